@@ -0,0 +1,105 @@
+/*
+ * Going the other way: from a picture to a program that draws it
+ */
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::{
+    cpu::{Instruction, Registers, INSTRUCTION_SET},
+    grid::Grid,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssembleError {
+    /// The first pixel (in drawing order) no program can get right
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Display for AssembleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "No program can draw pixel ({}, {})", self.x, self.y)
+    }
+}
+
+/// Find a program of `noop`s and `addx`s that draws `target`, one pixel per
+/// cycle, on a screen of the same size.
+///
+/// Since X only changes once an `addx` has finished, it stays fixed for at
+/// least two cycles at a time, and not every picture can be drawn. We figure
+/// out which values X can have at the start of every instruction, and walk
+/// back from the end of the screen to recover the instructions that got us
+/// there.
+pub fn assemble(target: &Grid) -> Result<Vec<Instruction>, AssembleError> {
+    let cycles = target.width * target.height;
+
+    // A sprite further off-screen than this looks exactly the same
+    let min_x = -2;
+    let max_x = target.width as i32 + 1;
+    let values = (max_x - min_x + 1) as usize;
+    let index = |x: i32| (x - min_x) as usize;
+
+    let draws_target = |cycle: usize, register: i32| {
+        let (x, y) = (cycle % target.width, cycle / target.width);
+        (register - 1..=register + 1).contains(&(x as i32)) == target.get(x, y)
+    };
+
+    // `start[cycle][x]` holds how we got to an instruction starting at
+    // `cycle` with X equal to `x`: the previous cycle and value of X
+    let mut start: Vec<Vec<Option<(usize, i32)>>> = vec![vec![None; values]; cycles + 1];
+    let initial = Registers::default().x;
+    start[0][index(initial)] = Some((0, initial));
+
+    for cycle in 0..cycles {
+        for register in min_x..=max_x {
+            if start[cycle][index(register)].is_none() || !draws_target(cycle, register) {
+                continue;
+            }
+
+            // noop
+            start[cycle + 1][index(register)].get_or_insert((cycle, register));
+
+            // addx, which keeps X around for another cycle. Adding zero is
+            // just two noops, so leave that to the noop case.
+            if cycle + 1 < cycles && draws_target(cycle + 1, register) {
+                for next in (min_x..=max_x).filter(|&next| next != register) {
+                    start[cycle + 2][index(next)].get_or_insert((cycle, register));
+                }
+            }
+        }
+    }
+
+    let Some(mut register) = (min_x..=max_x).find(|&x| start[cycles][index(x)].is_some()) else {
+        let cycle = (0..cycles)
+            .rev()
+            .find(|&cycle| start[cycle].iter().any(Option::is_some))
+            .unwrap_or(0);
+
+        return Err(AssembleError {
+            x: cycle % target.width,
+            y: cycle / target.width,
+        });
+    };
+
+    let mut cycle = cycles;
+    let mut lines = Vec::new();
+
+    while cycle > 0 {
+        let (previous_cycle, previous) = start[cycle][index(register)].unwrap();
+
+        if cycle - previous_cycle == 1 {
+            lines.push(String::from("noop"));
+        } else {
+            lines.push(format!("addx {}", register - previous));
+        }
+
+        cycle = previous_cycle;
+        register = previous;
+    }
+
+    Ok(lines
+        .iter()
+        .rev()
+        .map(|line| Instruction::parse(line, INSTRUCTION_SET).unwrap())
+        .collect())
+}
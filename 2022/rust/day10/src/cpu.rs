@@ -0,0 +1,181 @@
+/*
+ * A tiny CPU, driven by a table of operations
+ */
+
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Registers {
+    pub x: i32,
+}
+
+impl Default for Registers {
+    fn default() -> Self {
+        Registers { x: 1 }
+    }
+}
+
+/// Where to continue after an instruction has finished executing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Next,
+    /// Jump relative to the current instruction. The puzzle's instruction set
+    /// doesn't have any jumps, but custom ones can.
+    #[allow(dead_code)]
+    Jump(isize),
+}
+
+/// A single entry in an instruction set. The effect is only applied to the
+/// registers once all of the instruction's cycles have elapsed.
+#[derive(Debug)]
+pub struct Operation {
+    pub mnemonic: &'static str,
+    pub cycles: usize,
+    pub arity: usize,
+    pub execute: fn(&mut Registers, &[i32]) -> Flow,
+}
+
+pub type InstructionSet = [Operation];
+
+/// The instruction set from the puzzle
+pub const INSTRUCTION_SET: &InstructionSet = &[
+    Operation {
+        mnemonic: "noop",
+        cycles: 1,
+        arity: 0,
+        execute: |_, _| Flow::Next,
+    },
+    Operation {
+        mnemonic: "addx",
+        cycles: 2,
+        arity: 1,
+        execute: |registers, args| {
+            registers.x += args[0];
+            Flow::Next
+        },
+    },
+];
+
+#[derive(Debug, Clone)]
+pub struct Instruction {
+    pub op: &'static Operation,
+    pub args: Vec<i32>,
+}
+
+impl PartialEq for Instruction {
+    fn eq(&self, other: &Self) -> bool {
+        self.op.mnemonic == other.op.mnemonic && self.args == other.args
+    }
+}
+
+impl Eq for Instruction {}
+
+impl Display for Instruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.op.mnemonic)?;
+
+        for arg in self.args.iter() {
+            write!(f, " {arg}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Instruction {
+    /// Parse a line like `addx 3`, looking up the mnemonic in the given
+    /// instruction set.
+    pub fn parse(line: &str, set: &'static InstructionSet) -> Result<Instruction, String> {
+        let mut parts = line.split_whitespace();
+        let mnemonic = parts.next().ok_or("Empty instruction")?;
+
+        let op = set
+            .iter()
+            .find(|op| op.mnemonic == mnemonic)
+            .ok_or(format!("Unknown instruction: {mnemonic}"))?;
+
+        let args = parts
+            .map(|arg| arg.parse::<i32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| format!("Invalid argument in `{line}`: {err}"))?;
+
+        if args.len() != op.arity {
+            return Err(format!(
+                "`{mnemonic}` takes {} argument(s), got {}",
+                op.arity,
+                args.len()
+            ));
+        }
+
+        Ok(Instruction { op, args })
+    }
+}
+
+/// The state of the CPU *during* a single cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tick<'a> {
+    /// 1-based cycle number
+    pub cycle: usize,
+    pub pc: usize,
+    pub registers: Registers,
+    pub instruction: &'a Instruction,
+}
+
+#[derive(Debug, Clone)]
+pub struct Cpu {
+    pub registers: Registers,
+    pub pc: usize,
+    pub cycle: usize,
+    program: Vec<Instruction>,
+    /// Number of cycles already spent on the current instruction
+    elapsed: usize,
+}
+
+impl Cpu {
+    pub fn new(program: Vec<Instruction>) -> Cpu {
+        Cpu {
+            registers: Registers::default(),
+            pc: 0,
+            cycle: 0,
+            program,
+            elapsed: 0,
+        }
+    }
+
+    /// Advance the CPU by a single cycle. Returns `None` once the program
+    /// counter has left the program.
+    pub fn step(&mut self) -> Option<Tick<'_>> {
+        let instruction = self.program.get(self.pc)?;
+
+        self.cycle += 1;
+        self.elapsed += 1;
+
+        let tick = Tick {
+            cycle: self.cycle,
+            pc: self.pc,
+            registers: self.registers,
+            instruction,
+        };
+
+        if self.elapsed >= instruction.op.cycles {
+            self.elapsed = 0;
+
+            match (instruction.op.execute)(&mut self.registers, &instruction.args) {
+                Flow::Next => self.pc += 1,
+                // Jumping to before the start of the program halts it
+                Flow::Jump(offset) => {
+                    self.pc = self.pc.checked_add_signed(offset).unwrap_or(usize::MAX)
+                }
+            }
+        }
+
+        Some(tick)
+    }
+
+    /// Run the program to completion, calling `hook` on every cycle
+    pub fn run(&mut self, mut hook: impl FnMut(&Tick)) {
+        while let Some(tick) = self.step() {
+            hook(&tick);
+        }
+    }
+}
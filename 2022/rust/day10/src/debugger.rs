@@ -0,0 +1,118 @@
+/*
+ * Stepping through a program one cycle at a time
+ */
+
+use std::{
+    fmt::{self, Display, Formatter},
+    io::{self, BufRead, Write},
+};
+
+use crate::{
+    cpu::{Cpu, Tick},
+    Screen,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Breakpoint {
+    /// Break during the given (1-based) cycle
+    Cycle(usize),
+    /// Break during every cycle where X holds the given value
+    Register(i32),
+}
+
+impl Breakpoint {
+    fn hit(&self, tick: &Tick) -> bool {
+        match self {
+            Breakpoint::Cycle(cycle) => tick.cycle == *cycle,
+            Breakpoint::Register(value) => tick.registers.x == *value,
+        }
+    }
+}
+
+impl Display for Breakpoint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Breakpoint::Cycle(cycle) => write!(f, "cycle {cycle}"),
+            Breakpoint::Register(value) => write!(f, "X = {value}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Debugger {
+    /// Print every single cycle, rather than just the ones we break on
+    pub trace: bool,
+    pub breakpoints: Vec<Breakpoint>,
+}
+
+impl Debugger {
+    pub fn is_active(&self) -> bool {
+        self.trace || !self.breakpoints.is_empty()
+    }
+
+    /// Run the program, printing the trace to `output`. Whenever we hit a
+    /// breakpoint, we read the next command from `input`: step a single
+    /// cycle, continue to the next breakpoint, or quit.
+    pub fn run(
+        &self,
+        cpu: &mut Cpu,
+        screen: Screen,
+        mut input: impl BufRead,
+        mut output: impl Write,
+    ) -> io::Result<()> {
+        let mut stepping = false;
+
+        while let Some(tick) = cpu.step() {
+            let breakpoint = self.breakpoints.iter().find(|bp| bp.hit(&tick));
+
+            if self.trace || stepping || breakpoint.is_some() {
+                writeln!(output, "{}", trace_line(&tick, screen))?;
+            }
+
+            if let Some(breakpoint) = breakpoint {
+                writeln!(
+                    output,
+                    "Hit breakpoint {breakpoint} at cycle {}",
+                    tick.cycle
+                )?;
+            }
+
+            if !stepping && breakpoint.is_none() {
+                continue;
+            }
+
+            write!(output, "(s)tep, (c)ontinue or (q)uit? ")?;
+            output.flush()?;
+
+            let mut command = String::new();
+
+            // Running out of input means there's nobody left to ask
+            if input.read_line(&mut command)? == 0 {
+                stepping = false;
+                continue;
+            }
+
+            match command.trim() {
+                "" | "s" | "step" => stepping = true,
+                "q" | "quit" => return Ok(()),
+                _ => stepping = false,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn trace_line(tick: &Tick, screen: Screen) -> String {
+    let pixel = match screen.pixel(tick.cycle, tick.registers.x) {
+        Some((x, y, lit)) => format!("({x}, {y}) {}", if lit { '#' } else { '.' }),
+        None => String::from("off screen"),
+    };
+
+    format!(
+        "cycle {} | {:<8} | X = {} | pixel {pixel}",
+        tick.cycle,
+        tick.instruction.to_string(),
+        tick.registers.x
+    )
+}
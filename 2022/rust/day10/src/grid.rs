@@ -0,0 +1,124 @@
+/*
+ * A grid of pixels that are either lit or dark
+ */
+
+use std::{
+    fmt::{self, Display, Formatter},
+    io::Write,
+    str::FromStr,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid {
+    pub width: usize,
+    pub height: usize,
+    pixels: Vec<bool>,
+}
+
+impl Grid {
+    /// A completely dark grid
+    pub fn new(width: usize, height: usize) -> Grid {
+        Grid {
+            width,
+            height,
+            pixels: vec![false; width * height],
+        }
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, lit: bool) {
+        self.pixels[y * self.width + x] = lit;
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[bool]> {
+        // Not `chunks`, which can't cope with a grid zero pixels wide
+        (0..self.height).map(move |y| &self.pixels[y * self.width..(y + 1) * self.width])
+    }
+
+    /// Plain (ASCII) PBM, where lit pixels are black
+    pub fn to_pbm(&self) -> String {
+        let mut out = format!("P1\n{} {}\n", self.width, self.height);
+
+        for row in self.rows() {
+            let line = row
+                .iter()
+                .map(|&lit| if lit { "1" } else { "0" })
+                .collect::<Vec<_>>()
+                .join(" ");
+
+            out.push_str(&line);
+            out.push('\n');
+        }
+
+        out
+    }
+
+    /// Grayscale PNG with lit pixels drawn white on black. Every pixel gets
+    /// blown up to a `scale` × `scale` square, so the result is actually
+    /// legible.
+    pub fn write_png(&self, writer: impl Write, scale: usize) -> Result<(), png::EncodingError> {
+        let width = self.width * scale;
+        let height = self.height * scale;
+
+        let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let data: Vec<u8> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x / scale, y / scale)))
+            .map(|(x, y)| if self.get(x, y) { 255 } else { 0 })
+            .collect();
+
+        encoder.write_header()?.write_image_data(&data)
+    }
+}
+
+impl Display for Grid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let lines = self
+            .rows()
+            .map(|row| {
+                row.iter()
+                    .map(|&lit| if lit { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>();
+
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+impl FromStr for Grid {
+    type Err = String;
+
+    /// Read a grid drawn with `#`s and `.`s, like the CRT output
+    fn from_str(input: &str) -> Result<Grid, Self::Err> {
+        let rows: Vec<&str> = input.lines().collect();
+        let width = rows.first().map_or(0, |row| row.len());
+
+        if width == 0 {
+            return Err(String::from("A bitmap needs at least one pixel"));
+        }
+
+        let mut grid = Grid::new(width, rows.len());
+
+        for (y, row) in rows.iter().enumerate() {
+            if row.len() != width {
+                return Err(format!("Row {y} should be {width} pixels wide"));
+            }
+
+            for (x, c) in row.chars().enumerate() {
+                match c {
+                    '#' => grid.set(x, y, true),
+                    '.' => {}
+                    _ => return Err(format!("Invalid pixel {c:?} at ({x}, {y})")),
+                }
+            }
+        }
+
+        Ok(grid)
+    }
+}
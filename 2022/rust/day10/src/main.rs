@@ -1,86 +1,208 @@
-#![feature(iter_array_chunks)]
-use std::fs::read_to_string;
+mod assembler;
+mod cpu;
+mod debugger;
+mod grid;
+mod ocr;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum Instruction {
-    Noop,
-    Addx(i32),
+use std::{
+    env,
+    fs::{read_to_string, File},
+    io,
+};
+
+use cpu::{Cpu, Instruction, INSTRUCTION_SET};
+use debugger::{Breakpoint, Debugger};
+use grid::Grid;
+
+fn parse_input(input: &str) -> Vec<Instruction> {
+    input
+        .lines()
+        .map(|line| Instruction::parse(line, INSTRUCTION_SET).unwrap())
+        .collect()
 }
 
-impl From<&str> for Instruction {
-    fn from(value: &str) -> Instruction {
-        match value {
-            "noop" => Instruction::Noop,
-            _ => {
-                let instr = value.split(" ").collect::<Vec<_>>();
-                let amount: i32 = instr[1].parse().unwrap(); // Error handling is for wimps
-                return Instruction::Addx(amount);
+/// The dimensions of the CRT. Every cycle draws a single pixel, left to right
+/// and top to bottom.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Screen {
+    width: usize,
+    height: usize,
+}
+
+impl Default for Screen {
+    fn default() -> Self {
+        Screen {
+            width: 40,
+            height: 6,
+        }
+    }
+}
+
+impl Screen {
+    /// Draw the pixels for every cycle, given the X register values during
+    /// those cycles. Once the program has halted, the remaining pixels stay
+    /// dark.
+    fn render(&self, register_values: &[i32]) -> Grid {
+        let mut grid = Grid::new(self.width, self.height);
+
+        for (cycle, register) in register_values.iter().enumerate() {
+            if let Some((x, y, lit)) = self.pixel(cycle + 1, *register) {
+                grid.set(x, y, lit);
             }
         }
+
+        grid
+    }
+
+    /// The pixel drawn during a (1-based) cycle, and whether it's lit by a
+    /// sprite centered on `register`. `None` once we've run off the screen.
+    fn pixel(&self, cycle: usize, register: i32) -> Option<(usize, usize, bool)> {
+        let (x, y) = ((cycle - 1) % self.width, (cycle - 1) / self.width);
+        let lit = (register - 1..=register + 1).contains(&(x as i32));
+
+        (y < self.height).then_some((x, y, lit))
     }
 }
 
-fn parse_input(input: &str) -> Vec<Instruction> {
-    input.lines().map(|line| Instruction::from(line)).collect()
+/// The (1-based) cycles at which we measure the signal strength: `first`,
+/// and then every `interval` cycles after that.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SampleCycles {
+    first: usize,
+    interval: usize,
+}
+
+impl Default for SampleCycles {
+    fn default() -> Self {
+        SampleCycles {
+            first: 20,
+            interval: 40,
+        }
+    }
+}
+
+impl SampleCycles {
+    fn contains(&self, cycle: usize) -> bool {
+        cycle >= self.first && (cycle - self.first).is_multiple_of(self.interval)
+    }
 }
 
 fn part1(input: &str) -> i32 {
-    let instructions = parse_input(&input);
-    let register_values = get_register_values(instructions);
+    signal_strength(
+        &get_register_values(parse_input(input)),
+        SampleCycles::default(),
+    )
+}
 
+fn signal_strength(register_values: &[i32], samples: SampleCycles) -> i32 {
     register_values
         .iter()
         .enumerate()
         .map(|(i, val)| (i + 1, val)) // change indices to be 1-based
-        .filter(|(i, _)| i % 40 == 20) // Only consider cycle 20, 60, 100, etc...
+        .filter(|(i, _)| samples.contains(*i))
         .map(|(i, val)| (i as i32) * val) // Calculate score
         .sum()
 }
 
+/// The value of the X register during every cycle of the program
 fn get_register_values(instructions: Vec<Instruction>) -> Vec<i32> {
-    let cycle_updates = instructions
-        .into_iter()
-        .flat_map(|instruction| match instruction {
-            Instruction::Noop => vec![0].into_iter(),
-            Instruction::Addx(val) => vec![0, val].into_iter(),
-        });
-
-    cycle_updates
-        .scan(1, |acc, val| {
-            let current = *acc;
-            *acc = *acc + val;
-
-            Some(current)
-        })
-        .collect()
-}
+    let mut values = Vec::new();
+    Cpu::new(instructions).run(|tick| values.push(tick.registers.x));
 
-fn part2(input: &str) -> String {
-    let instructions = parse_input(input);
-    let register_values = get_register_values(instructions);
-    let clock_cycles = 0..279;
-    let pixels = clock_cycles
-        .zip(register_values.iter())
-        // Convert to pixel values
-        .map(|(cycle, register)| {
-            if (register - 1..=register + 1).contains(&(cycle % 40)) {
-                '#'
-            } else {
-                '.'
-            }
-        })
-        .array_chunks::<40>() // Chunk into 40 character lines
-        .map(|line_chars| line_chars.iter().collect::<String>()) // Collect 40 chars into string
-        .collect::<Vec<_>>()
-        .join("\n");
+    values
+}
 
-    pixels
+/// What the program draws on the default screen
+fn part2(input: &str) -> Grid {
+    Screen::default().render(&get_register_values(parse_input(input)))
 }
 
 fn main() {
     let input = read_to_string("./src/input.txt").unwrap();
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let mut debugger = Debugger::default();
+    let mut png = None;
+    let mut pbm = None;
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--trace" => debugger.trace = true,
+            "--break-cycle" => debugger.breakpoints.push(Breakpoint::Cycle(
+                args.next()
+                    .and_then(|n| n.parse().ok())
+                    .expect("Invalid cycle"),
+            )),
+            "--break-x" => debugger.breakpoints.push(Breakpoint::Register(
+                args.next()
+                    .and_then(|n| n.parse().ok())
+                    .expect("Invalid register value"),
+            )),
+            "--assemble" => {
+                let path = args.next().expect("Missing path to a bitmap");
+                let target: Grid =
+                    match read_to_string(path).expect("Failed to read bitmap").parse() {
+                        Ok(target) => target,
+                        Err(err) => {
+                            eprintln!("Invalid bitmap: {err}");
+                            std::process::exit(1);
+                        }
+                    };
+
+                match assembler::assemble(&target) {
+                    Ok(program) => program.iter().for_each(|instr| println!("{instr}")),
+                    Err(err) => {
+                        eprintln!("{err}");
+                        std::process::exit(1);
+                    }
+                }
+
+                return;
+            }
+            // Optionally save the screen as an image
+            "--png" => png = args.next(),
+            "--pbm" => pbm = args.next(),
+            _ => {
+                eprintln!(
+                    "Usage: day10 [--trace] [--break-cycle <n>] [--break-x <n>] [--png <path>] [--pbm <path>]\n       day10 --assemble <bitmap>"
+                );
+                std::process::exit(1);
+            }
+        }
+    }
+
+    if debugger.is_active() {
+        let mut cpu = Cpu::new(parse_input(&input));
+        debugger
+            .run(
+                &mut cpu,
+                Screen::default(),
+                io::stdin().lock(),
+                io::stdout(),
+            )
+            .expect("Failed to run the debugger");
+        return;
+    }
+
     println!("Solution to part 1 is: {}", part1(&input));
-    println!("Solution to part 2 is:\n{}", part2(&input));
+    let grid = part2(&input);
+    let screen = grid.to_string();
+    println!("Solution to part 2 is:\n{}", screen);
+
+    match ocr::decode(&screen) {
+        Ok(letters) => println!("Which reads: {letters}"),
+        Err(err) => eprintln!("Couldn't read the screen: {err}"),
+    }
+
+    if let Some(path) = png {
+        let file = File::create(path).expect("Failed to create image");
+        grid.write_png(file, 10).expect("Failed to write PNG");
+    }
+
+    if let Some(path) = pbm {
+        std::fs::write(path, grid.to_pbm()).expect("Failed to write PBM");
+    }
 }
 
 #[test]
@@ -88,3 +210,222 @@ fn part1_works() {
     let input = read_to_string("./src/test-input.txt").unwrap();
     assert_eq!(part1(&input), 13140);
 }
+
+#[test]
+fn part2_works() {
+    let input = read_to_string("./src/input.txt").unwrap();
+    assert_eq!(
+        ocr::decode(&part2(&input).to_string()),
+        Ok(String::from("ERCREPCJ"))
+    );
+
+    // The example doesn't spell out anything
+    let input = read_to_string("./src/test-input.txt").unwrap();
+    assert_eq!(
+        ocr::decode(&part2(&input).to_string()),
+        Err(ocr::OcrError::Unrecognised((0..8).collect()))
+    );
+}
+
+#[test]
+fn non_default_screen() {
+    // X stays at 1, so the sprite covers the first three pixels of every row
+    let values = vec![1; 24];
+    let screen = Screen {
+        width: 8,
+        height: 3,
+    };
+    assert_eq!(
+        screen.render(&values).to_string(),
+        "###.....\n###.....\n###....."
+    );
+
+    // Pixels after the program halted stay dark
+    let screen = Screen {
+        width: 5,
+        height: 6,
+    };
+    assert_eq!(
+        screen.render(&values).to_string(),
+        "###..\n###..\n###..\n###..\n###..\n.....",
+    );
+}
+
+#[test]
+fn grid_images() {
+    let mut grid = Grid::new(3, 2);
+    grid.set(0, 0, true);
+    grid.set(2, 1, true);
+
+    assert_eq!(grid.to_string(), "#..\n..#");
+    assert_eq!(grid.to_pbm(), "P1\n3 2\n1 0 0\n0 0 1\n");
+
+    let mut png = Vec::new();
+    grid.write_png(&mut png, 2).unwrap();
+
+    let mut reader = png::Decoder::new(png.as_slice()).read_info().unwrap();
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data).unwrap();
+
+    assert_eq!((info.width, info.height), (6, 4));
+    assert_eq!(&data[..6], &[255, 255, 0, 0, 0, 0]);
+    assert_eq!(&data[18..], &[0, 0, 0, 0, 255, 255]);
+}
+
+#[test]
+fn empty_grids() {
+    assert!("".parse::<Grid>().is_err());
+    assert!("\n\n".parse::<Grid>().is_err());
+
+    // A grid with no columns still has its rows
+    let grid = Grid::new(0, 3);
+    assert_eq!(grid.rows().count(), 3);
+    assert_eq!(grid.to_string(), "\n\n");
+    assert_eq!(grid.to_pbm(), "P1\n0 3\n\n\n\n");
+}
+
+#[test]
+fn custom_sample_cycles() {
+    let values = get_register_values(parse_input("noop\naddx 3\naddx -5"));
+    assert_eq!(values, vec![1, 1, 1, 4, 4]);
+
+    let samples = SampleCycles {
+        first: 1,
+        interval: 2,
+    };
+    assert_eq!(signal_strength(&values, samples), 1 + 3 + 5 * 4);
+}
+
+#[test]
+fn custom_instruction_set() {
+    use cpu::{Flow, Operation};
+
+    // Multiply X in three cycles, and jump back by 2 while X is below 100
+    const SET: &[Operation] = &[
+        Operation {
+            mnemonic: "mulx",
+            cycles: 3,
+            arity: 1,
+            execute: |registers, args| {
+                registers.x *= args[0];
+                Flow::Next
+            },
+        },
+        Operation {
+            mnemonic: "jlt",
+            cycles: 1,
+            arity: 2,
+            execute: |registers, args| {
+                if registers.x < args[0] {
+                    Flow::Jump(args[1] as isize)
+                } else {
+                    Flow::Next
+                }
+            },
+        },
+    ];
+
+    let program = ["mulx 3", "jlt 100 -1"]
+        .iter()
+        .map(|line| Instruction::parse(line, SET).unwrap())
+        .collect();
+
+    let mut cpu = Cpu::new(program);
+    let mut cycles = 0;
+    cpu.run(|_| cycles += 1);
+
+    // 1 -> 3 -> 9 -> 27 -> 81 -> 243, four cycles per multiplication
+    assert_eq!(cpu.registers.x, 243);
+    assert_eq!(cycles, 5 * 4);
+    assert!(Instruction::parse("addx 1", SET).is_err());
+}
+
+#[test]
+fn debugger_trace_and_breakpoints() {
+    let program = parse_input("noop\naddx 3\naddx -5\nnoop");
+    let screen = Screen {
+        width: 4,
+        height: 2,
+    };
+
+    let debugger = Debugger {
+        trace: true,
+        breakpoints: vec![Breakpoint::Register(4)],
+    };
+
+    // Step once at the breakpoint, then continue to the end
+    let mut output = Vec::new();
+    debugger
+        .run(
+            &mut Cpu::new(program.clone()),
+            screen,
+            "s\nc\n".as_bytes(),
+            &mut output,
+        )
+        .unwrap();
+    let output = String::from_utf8(output).unwrap();
+
+    assert_eq!(
+        output.lines().collect::<Vec<_>>(),
+        vec![
+            "cycle 1 | noop     | X = 1 | pixel (0, 0) #",
+            "cycle 2 | addx 3   | X = 1 | pixel (1, 0) #",
+            "cycle 3 | addx 3   | X = 1 | pixel (2, 0) #",
+            "cycle 4 | addx -5  | X = 4 | pixel (3, 0) #",
+            "Hit breakpoint X = 4 at cycle 4",
+            "(s)tep, (c)ontinue or (q)uit? cycle 5 | addx -5  | X = 4 | pixel (0, 1) .",
+            "Hit breakpoint X = 4 at cycle 5",
+            "(s)tep, (c)ontinue or (q)uit? cycle 6 | noop     | X = -1 | pixel (1, 1) .",
+        ]
+    );
+
+    // Quitting stops the program right at the breakpoint
+    let debugger = Debugger {
+        trace: false,
+        breakpoints: vec![Breakpoint::Cycle(2)],
+    };
+    let mut cpu = Cpu::new(program);
+    debugger
+        .run(&mut cpu, screen, "q\n".as_bytes(), &mut Vec::new())
+        .unwrap();
+    assert_eq!(cpu.cycle, 2);
+}
+
+#[test]
+fn assembled_programs_draw_their_target() {
+    for path in ["./src/input.txt", "./src/test-input.txt"] {
+        let target = part2(&read_to_string(path).unwrap());
+        // Which survives a round trip through text
+        assert_eq!(target.to_string().parse::<Grid>().unwrap(), target);
+
+        let program = assembler::assemble(&target).unwrap();
+        let source = program
+            .iter()
+            .map(|instruction| instruction.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        assert_eq!(part2(&source), target);
+    }
+}
+
+#[test]
+fn assembling_impossible_pictures() {
+    // X starts out at 1, so the first two pixels are always lit
+    let dark = Grid::new(40, 6);
+    assert_eq!(
+        assembler::assemble(&dark),
+        Err(assembler::AssembleError { x: 0, y: 0 })
+    );
+
+    // The earliest X can change is after the first two cycles
+    let target: Grid = "#.#.#.#.\n########".parse().unwrap();
+    assert_eq!(
+        assembler::assemble(&target),
+        Err(assembler::AssembleError { x: 1, y: 0 })
+    );
+
+    // But every other pixel is fine after that
+    let target: Grid = "###.#.#.".parse().unwrap();
+    assert!(assembler::assemble(&target).is_ok());
+}
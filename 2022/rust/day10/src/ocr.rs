@@ -0,0 +1,89 @@
+/*
+ * Reading letters off the CRT
+ */
+
+use std::fmt::{self, Display, Formatter};
+
+const GLYPH_WIDTH: usize = 4;
+const GLYPH_HEIGHT: usize = 6;
+
+/// Every glyph takes up 4 columns, followed by a single blank column
+const CELL_WIDTH: usize = GLYPH_WIDTH + 1;
+
+/// The letters that show up in AoC puzzles, each drawn as 6 rows of 4 pixels
+const FONT: &[(char, [&str; GLYPH_HEIGHT])] = &[
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Y', ["#...", "#...", ".#.#", "..#.", "..#.", "..#."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OcrError {
+    /// The screen isn't made up of 6 equally long rows
+    Malformed,
+    /// The (0-based) positions of the glyphs we couldn't match to a letter
+    Unrecognised(Vec<usize>),
+}
+
+impl Display for OcrError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            OcrError::Malformed => write!(f, "Screen should consist of {GLYPH_HEIGHT} equal rows"),
+            OcrError::Unrecognised(positions) => {
+                write!(f, "Unrecognised glyphs at positions {positions:?}")
+            }
+        }
+    }
+}
+
+/// Turn a rendered screen of `#`s and `.`s into the letters it spells out
+pub fn decode(screen: &str) -> Result<String, OcrError> {
+    let rows: Vec<&[u8]> = screen.lines().map(str::as_bytes).collect();
+
+    if rows.len() != GLYPH_HEIGHT || rows.iter().any(|row| row.len() != rows[0].len()) {
+        return Err(OcrError::Malformed);
+    }
+
+    let glyphs = rows[0].len().div_ceil(CELL_WIDTH);
+    let mut letters = String::new();
+    let mut unrecognised = Vec::new();
+
+    for position in 0..glyphs {
+        let start = position * CELL_WIDTH;
+        let end = usize::min(start + GLYPH_WIDTH, rows[0].len());
+        let glyph: Vec<&[u8]> = rows.iter().map(|row| &row[start..end]).collect();
+
+        let letter = FONT.iter().find(|(_, pixels)| {
+            pixels
+                .iter()
+                .zip(glyph.iter())
+                .all(|(a, b)| a.as_bytes() == *b)
+        });
+
+        match letter {
+            Some((letter, _)) => letters.push(*letter),
+            None => unrecognised.push(position),
+        }
+    }
+
+    if unrecognised.is_empty() {
+        Ok(letters)
+    } else {
+        Err(OcrError::Unrecognised(unrecognised))
+    }
+}
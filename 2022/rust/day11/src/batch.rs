@@ -0,0 +1,76 @@
+/*
+ * Simulating a whole directory of troops in parallel
+ */
+
+use std::{
+    fs::{read_dir, read_to_string},
+    io,
+    path::{Path, PathBuf},
+};
+
+use rayon::prelude::*;
+
+use crate::{parse_troop, SimulationConfig};
+
+/// The monkey business for a single input file, or the reason we couldn't
+/// compute it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchResult {
+    pub path: PathBuf,
+    pub monkey_business: Result<u128, String>,
+}
+
+/// Simulate every file in `dir` on all available cores. A file that can't be
+/// read, parsed or simulated without overflowing doesn't stop the batch, it
+/// just reports an error.
+pub fn run(dir: &Path, config: SimulationConfig) -> io::Result<Vec<BatchResult>> {
+    let mut paths = read_dir(dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    paths.retain(|path| path.is_file());
+    paths.sort();
+
+    let results = paths
+        .into_par_iter()
+        .map(|path| {
+            let monkey_business = read_to_string(&path)
+                .map_err(|err| err.to_string())
+                .and_then(|input| parse_troop(&input))
+                .and_then(|mut troop| troop.simulate(config, None).map_err(|err| err.to_string()));
+
+            BatchResult {
+                path,
+                monkey_business,
+            }
+        })
+        .collect();
+
+    Ok(results)
+}
+
+/// One line per input, followed by the number of inputs that failed
+pub fn summary(results: &[BatchResult]) -> String {
+    let mut out = String::new();
+
+    for result in results {
+        let outcome = match &result.monkey_business {
+            Ok(value) => value.to_string(),
+            Err(err) => format!("error: {err}"),
+        };
+
+        out.push_str(&format!("{}: {outcome}\n", result.path.display()));
+    }
+
+    let failed = results
+        .iter()
+        .filter(|result| result.monkey_business.is_err())
+        .count();
+
+    out.push_str(&format!(
+        "Simulated {} inputs, {failed} failed\n",
+        results.len()
+    ));
+
+    out
+}
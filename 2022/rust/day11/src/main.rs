@@ -4,7 +4,17 @@
  * This is where I wish I was implementing this as a lisp...
  */
 
-use std::{cmp::Ordering, fs::read_to_string};
+mod batch;
+mod report;
+
+use std::{
+    cmp::Ordering,
+    collections::VecDeque,
+    env,
+    fmt::{self, Display, Formatter},
+    fs::read_to_string,
+    path::Path,
+};
 
 use nom::{
     branch::alt,
@@ -19,7 +29,8 @@ use nom::{
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Monkey {
     activity: u128,
-    items: Vec<Item>,
+    /// Items are inspected and thrown in the order they were received
+    items: VecDeque<Item>,
     worry_update: Op,
     test: Test,
 }
@@ -32,31 +43,37 @@ impl Ord for Monkey {
 
 impl PartialOrd for Monkey {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.activity.cmp(&other.activity))
+        Some(self.cmp(other))
     }
 }
 
 impl Monkey {
     fn test(&self, item: &Item) -> usize {
-        if item.0 % self.test.modulo == 0 {
+        if item.0.is_multiple_of(self.test.modulo) {
             self.test.if_true as usize
         } else {
             self.test.if_false as usize
         }
     }
 
-    fn update_items(&mut self, worry_factor: u128, modulus: u128) {
-        self.items.iter_mut().for_each(|item| {
+    /// Inspect every item, or return `None` if a worry level overflows
+    fn update_items(&mut self, worry_factor: u128, modulus: u128) -> Option<()> {
+        for item in self.items.iter_mut() {
             self.activity += 1;
 
-            let new_value = match &self.worry_update {
-                Op::Add(num) => item.0 + num,
-                Op::Mult(num) => item.0 * num,
-                Op::Square => item.0 * item.0,
-            } as u128;
+            let new_value = self.worry_update.apply(item.0)?;
+
+            // Integer division doesn't respect remainders, so we can only
+            // keep worry levels small when there's no relief. Otherwise we
+            // need the exact values.
+            item.0 = if worry_factor == 1 {
+                new_value % modulus
+            } else {
+                new_value / worry_factor
+            };
+        }
 
-            item.0 = (new_value / worry_factor as u128) % modulus;
-        });
+        Some(())
     }
 }
 
@@ -67,6 +84,17 @@ enum Op {
     Square,
 }
 
+impl Op {
+    /// The new worry level, or `None` if it overflows
+    fn apply(&self, old: u128) -> Option<u128> {
+        match self {
+            Op::Add(num) => old.checked_add(*num),
+            Op::Mult(num) => old.checked_mul(*num),
+            Op::Square => old.checked_mul(old),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Test {
     modulo: u128,
@@ -77,6 +105,308 @@ struct Test {
 #[derive(Debug, Clone, PartialEq, Eq)]
 struct Item(u128);
 
+/// A validated group of monkeys, together with the modulus we use to keep
+/// worry levels from overflowing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Troop {
+    monkeys: Vec<Monkey>,
+    modulus: u128,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TroopError {
+    /// A monkey throws to a monkey that doesn't exist
+    DanglingReference { monkey: usize, target: u128 },
+    /// A monkey throws items to itself
+    SelfThrow { monkey: usize },
+    /// A monkey tests for divisibility by zero
+    ZeroDivisor { monkey: usize },
+    /// The least common multiple of the moduli doesn't fit in a `u128`
+    ModulusOverflow,
+    /// A monkey's operation overflows a `u128` on the largest worry level it
+    /// can be handed, e.g. squaring with a modulus above `u64::MAX`
+    WorryOverflow { monkey: usize },
+}
+
+impl Display for TroopError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TroopError::DanglingReference { monkey, target } => {
+                write!(f, "Monkey {monkey} throws to non-existent monkey {target}")
+            }
+            TroopError::SelfThrow { monkey } => write!(f, "Monkey {monkey} throws to itself"),
+            TroopError::ZeroDivisor { monkey } => {
+                write!(f, "Monkey {monkey} tests for divisibility by zero")
+            }
+            TroopError::ModulusOverflow => {
+                write!(f, "The least common multiple of the moduli is too large")
+            }
+            TroopError::WorryOverflow { monkey } => {
+                write!(
+                    f,
+                    "Monkey {monkey}'s operation can overflow the worry level"
+                )
+            }
+        }
+    }
+}
+
+impl TryFrom<Vec<Monkey>> for Troop {
+    type Error = TroopError;
+
+    fn try_from(monkeys: Vec<Monkey>) -> Result<Troop, Self::Error> {
+        for (idx, monkey) in monkeys.iter().enumerate() {
+            if monkey.test.modulo == 0 {
+                return Err(TroopError::ZeroDivisor { monkey: idx });
+            }
+
+            for target in [monkey.test.if_true, monkey.test.if_false] {
+                if target >= monkeys.len() as u128 {
+                    return Err(TroopError::DanglingReference {
+                        monkey: idx,
+                        target,
+                    });
+                }
+
+                if target == idx as u128 {
+                    return Err(TroopError::SelfThrow { monkey: idx });
+                }
+            }
+        }
+
+        // SECRET SAUCE: We want to prevent overflows in the products. Without
+        // relief, the only thing we really care about is the worry factor
+        // modulo the test moduli. Hence, we can store the values modulo the
+        // least common multiple of all of these, and everyone will still get
+        // the correct remainders when doing their modulus checks. (For the
+        // prime moduli in the puzzle input, this is just their product.) This
+        // doesn't work with relief, since dividing doesn't preserve
+        // remainders, so then the values are kept exact instead.
+        let modulus = monkeys
+            .iter()
+            .try_fold(1, |acc, monkey| lcm(acc, monkey.test.modulo))
+            .ok_or(TroopError::ModulusOverflow)?;
+
+        // Without relief, every worry level is below the modulus after the
+        // first inspection, so the largest one a monkey ever sees is either
+        // that or one of the starting items. With relief, later overflows are
+        // caught during the simulation.
+        let largest = monkeys
+            .iter()
+            .flat_map(|monkey| monkey.items.iter().map(|item| item.0))
+            .fold(modulus - 1, u128::max);
+
+        for (idx, monkey) in monkeys.iter().enumerate() {
+            if monkey.worry_update.apply(largest).is_none() {
+                return Err(TroopError::WorryOverflow { monkey: idx });
+            }
+        }
+
+        Ok(Troop { monkeys, modulus })
+    }
+}
+
+impl Troop {
+    /// Play a single round. When a `History` is passed in, the round's
+    /// inspections, transfers and worry levels get appended to it.
+    fn play_round(&mut self, denom: u128, history: Option<&mut History>) -> Result<(), TroopError> {
+        let mut stats = history
+            .as_ref()
+            .map(|_| RoundStats::new(self.monkeys.len()));
+
+        for idx in 0..self.monkeys.len() {
+            self.play_turn(idx, denom, stats.as_mut())?;
+        }
+
+        if let (Some(history), Some(stats)) = (history, stats) {
+            history.rounds.push(stats);
+        }
+
+        Ok(())
+    }
+
+    fn play_turn(
+        &mut self,
+        n: usize,
+        denom: u128,
+        mut stats: Option<&mut RoundStats>,
+    ) -> Result<(), TroopError> {
+        let activity = self.monkeys[n].activity;
+        self.monkeys[n]
+            .update_items(denom, self.modulus)
+            .ok_or(TroopError::WorryOverflow { monkey: n })?;
+
+        if let Some(stats) = stats.as_mut() {
+            stats.inspections[n] = self.monkeys[n].activity - activity;
+        }
+
+        while let Some(item) = self.monkeys[n].items.pop_front() {
+            let recipient = self.monkeys[n].test(&item);
+
+            if let Some(stats) = stats.as_mut() {
+                stats.record_throw(n, recipient, &item);
+            }
+
+            self.monkeys[recipient].items.push_back(item);
+        }
+
+        Ok(())
+    }
+
+    /// Play all the rounds in the config and return the monkey business. With
+    /// relief, worry levels are kept exact and can overflow, which is
+    /// reported as an error rather than giving a wrong answer.
+    fn simulate(
+        &mut self,
+        config: SimulationConfig,
+        mut history: Option<&mut History>,
+    ) -> Result<u128, TroopError> {
+        for _ in 0..config.rounds {
+            self.play_round(config.relief, history.as_deref_mut())?;
+        }
+
+        Ok(self.monkey_business(config.top))
+    }
+
+    /// The product of the activities of the `top` most active monkeys
+    fn monkey_business(&self, top: usize) -> u128 {
+        let mut monkeys = self.monkeys.clone();
+        monkeys.sort();
+
+        monkeys
+            .into_iter()
+            .rev()
+            .take(top)
+            .map(|monkey| monkey.activity)
+            .product()
+    }
+}
+
+/// The knobs of a simulation: how many rounds to play, how much the worry
+/// levels get divided by after each inspection, and how many of the most
+/// active monkeys make up the monkey business.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SimulationConfig {
+    rounds: usize,
+    relief: u128,
+    top: usize,
+}
+
+impl SimulationConfig {
+    const PART1: SimulationConfig = SimulationConfig {
+        rounds: 20,
+        relief: 3,
+        top: 2,
+    };
+
+    const PART2: SimulationConfig = SimulationConfig {
+        rounds: 10000,
+        relief: 1,
+        top: 2,
+    };
+
+    /// Parse `--rounds`, `--relief` and `--top` flags on top of the part 1
+    /// configuration. Any other arguments are left alone.
+    fn from_args(args: &[String]) -> Result<SimulationConfig, String> {
+        let mut config = SimulationConfig::PART1;
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let flag = arg.as_str();
+
+            if !["--rounds", "--relief", "--top"].contains(&flag) {
+                continue;
+            }
+
+            let value = args
+                .next()
+                .ok_or(format!("Missing value for {flag}"))?
+                .parse::<usize>()
+                .map_err(|err| format!("Invalid value for {flag}: {err}"))?;
+
+            match flag {
+                "--rounds" => config.rounds = value,
+                "--relief" if value == 0 => return Err(String::from("Relief can't be zero")),
+                "--relief" => config.relief = value as u128,
+                "--top" if value == 0 => return Err(String::from("Top can't be zero")),
+                _ => config.top = value,
+            }
+        }
+
+        Ok(config)
+    }
+}
+
+fn gcd(a: u128, b: u128) -> u128 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// The least common multiple, or `None` if it doesn't fit in a `u128`
+fn lcm(a: u128, b: u128) -> Option<u128> {
+    (a / gcd(a, b)).checked_mul(b)
+}
+
+/// Everything we observed while playing a number of rounds, one entry per
+/// round.
+#[derive(Debug, Clone, Default)]
+struct History {
+    rounds: Vec<RoundStats>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RoundStats {
+    /// Number of items each monkey inspected during this round
+    inspections: Vec<u128>,
+    /// `transfers[from][to]` is the number of items `from` threw to `to`
+    transfers: Vec<Vec<u128>>,
+    /// Number of thrown items per worry bucket, where bucket `k` holds the
+    /// items whose worry level needs exactly `k` bits (i.e., is in
+    /// `2^(k-1)..2^k`, or is 0 for `k = 0`)
+    worry: Vec<u128>,
+}
+
+impl RoundStats {
+    fn new(monkeys: usize) -> RoundStats {
+        RoundStats {
+            inspections: vec![0; monkeys],
+            transfers: vec![vec![0; monkeys]; monkeys],
+            worry: Vec::new(),
+        }
+    }
+
+    fn record_throw(&mut self, from: usize, to: usize, item: &Item) {
+        self.transfers[from][to] += 1;
+
+        let bucket = (u128::BITS - item.0.leading_zeros()) as usize;
+        if self.worry.len() <= bucket {
+            self.worry.resize(bucket + 1, 0);
+        }
+        self.worry[bucket] += 1;
+    }
+}
+
+impl History {
+    /// Total number of items each monkey threw to each other monkey
+    fn transfer_matrix(&self) -> Vec<Vec<u128>> {
+        let size = self.rounds.first().map_or(0, |round| round.transfers.len());
+        let mut matrix = vec![vec![0; size]; size];
+
+        for round in self.rounds.iter() {
+            for (from, row) in round.transfers.iter().enumerate() {
+                for (to, count) in row.iter().enumerate() {
+                    matrix[from][to] += count;
+                }
+            }
+        }
+
+        matrix
+    }
+}
+
 /*
  * Parsing
  */
@@ -99,13 +429,13 @@ fn monkey(input: &str) -> IResult<&str, Monkey> {
     .parse(input)
 }
 
-fn items(input: &str) -> IResult<&str, Vec<Item>> {
+fn items(input: &str) -> IResult<&str, VecDeque<Item>> {
     delimited(
         tag("  Starting items: "),
         separated_list1(tag(", "), u128),
         newline,
     )
-    .map(|levels| levels.into_iter().rev().map(Item).collect())
+    .map(|levels| levels.into_iter().map(Item).collect())
     .parse(input)
 }
 
@@ -151,78 +481,467 @@ fn if_false(input: &str) -> IResult<&str, u128> {
     delimited(tag("    If false: throw to monkey "), u128, newline)(input)
 }
 
-fn part1(input: &str) -> u128 {
-    let (_, mut monkeys) = parse_input(&input).unwrap();
+fn part1(input: &str) -> Result<u128, String> {
+    simulate(input, SimulationConfig::PART1, None)
+}
 
-    for _ in 0..20 {
-        play_round(&mut monkeys, 3);
-    }
+fn part2(input: &str) -> Result<u128, String> {
+    simulate(input, SimulationConfig::PART2, None)
+}
 
-    monkeys.sort();
+/// Run a simulation and return the resulting monkey business, optionally
+/// keeping track of the full history.
+fn simulate(
+    input: &str,
+    config: SimulationConfig,
+    history: Option<&mut History>,
+) -> Result<u128, String> {
+    parse_troop(input)?
+        .simulate(config, history)
+        .map_err(|err| err.to_string())
+}
 
-    monkeys
-        .into_iter()
-        .rev()
-        .take(2)
-        .map(|monkey| monkey.activity)
-        .product()
+/// The puzzle input, which only some of the commands need
+fn read_input() -> String {
+    or_exit(
+        read_to_string("./src/input.txt")
+            .map_err(|err| format!("Failed to read ./src/input.txt: {err}")),
+    )
 }
 
-fn part2(input: &str) -> u128 {
-    let (_, mut monkeys) = parse_input(&input).unwrap();
+/// Unwrap a result, or print the error and exit
+fn or_exit<T>(result: Result<T, String>) -> T {
+    result.unwrap_or_else(|err| {
+        eprintln!("{err}");
+        std::process::exit(1);
+    })
+}
 
-    for _ in 0..10000 {
-        play_round(&mut monkeys, 1);
+/// Parse and validate a set of monkey definitions
+fn parse_troop(input: &str) -> Result<Troop, String> {
+    let (_, monkeys) = parse_input(input).map_err(|err| err.to_string())?;
+    Troop::try_from(monkeys).map_err(|err| err.to_string())
+}
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let config = match SimulationConfig::from_args(&args) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    };
+
+    match args.first().map(String::as_str) {
+        // Flags without a subcommand are just as good
+        Some("simulate" | "--rounds" | "--relief" | "--top") => {
+            let input = read_input();
+            let monkey_business = or_exit(simulate(&input, config, None));
+            println!("The monkey business is: {monkey_business}");
+        }
+        Some("batch") => {
+            let Some(dir) = args.get(1) else {
+                eprintln!("Usage: day11 batch <dir> [--rounds N] [--relief N] [--top N]");
+                std::process::exit(1);
+            };
+
+            match batch::run(Path::new(dir), config) {
+                Ok(results) => print!("{}", batch::summary(&results)),
+                Err(err) => {
+                    eprintln!("Failed to read {dir}: {err}");
+                    std::process::exit(1);
+                }
+            }
+        }
+        Some("report") => {
+            let format = if args.iter().any(|arg| arg == "--csv") {
+                report::Format::Csv
+            } else {
+                report::Format::Table
+            };
+
+            let input = read_input();
+            let mut history = History::default();
+            or_exit(simulate(&input, config, Some(&mut history)));
+            print!("{}", report::render(&history, format));
+        }
+        None => {
+            let input = read_input();
+            println!("The solution to part 1 is: {}", or_exit(part1(&input)));
+            println!("The solution to part 2 is: {}", or_exit(part2(&input)));
+        }
+        Some(command) => {
+            eprintln!("Unknown command {command}, expected simulate, report or batch");
+            std::process::exit(1);
+        }
     }
+}
 
-    monkeys.sort();
+#[test]
+fn part1_works() {
+    let input = read_to_string("./src/test-input.txt").unwrap();
+    assert_eq!(part1(&input), Ok(10605));
+}
 
-    monkeys
-        .into_iter()
-        .rev()
-        .take(2)
-        .map(|monkey| monkey.activity)
-        .product()
+#[test]
+fn part2_works() {
+    let input = read_to_string("./src/test-input.txt").unwrap();
+    assert_eq!(part2(&input), Ok(2713310158));
 }
 
-fn play_round(monkeys: &mut Vec<Monkey>, denom: u128) {
-    for idx in 0..monkeys.len() {
-        play_turn(monkeys, idx, denom);
-    }
+#[test]
+fn history_matches_activity() {
+    let input = read_to_string("./src/test-input.txt").unwrap();
+    let mut history = History::default();
+    simulate(&input, SimulationConfig::PART1, Some(&mut history)).unwrap();
+
+    assert_eq!(history.rounds.len(), 20);
+
+    let totals: Vec<u128> = (0..4)
+        .map(|monkey| {
+            history
+                .rounds
+                .iter()
+                .map(|round| round.inspections[monkey])
+                .sum()
+        })
+        .collect();
+    assert_eq!(totals, vec![101, 95, 7, 105]);
+
+    // Every inspected item gets thrown exactly once
+    let thrown: Vec<u128> = history
+        .transfer_matrix()
+        .iter()
+        .map(|row| row.iter().sum())
+        .collect();
+    assert_eq!(thrown, totals);
 }
 
-fn play_turn(monkeys: &mut Vec<Monkey>, n: usize, denom: u128) {
-    // SECRET SAUNCE: We want to prevent overflows in the products. Notice that
-    // the only thing we really care about is the worry factor modulo the
-    // test modulus. Also notice they are all prime (this is the lame
-    // observation that I missed). Hence, we can store the values modulo the
-    // product of all of these, and (by the Chinese Remainder Theorem, remember
-    // that one?), everyone will still get the correct remainders when doing
-    // their modulus checks.
-    let crt_modulus = monkeys.iter().map(|monkey| monkey.test.modulo).product();
-    monkeys[n].update_items(denom, crt_modulus);
+#[test]
+fn troop_uses_lcm_of_moduli() {
+    let input = read_to_string("./src/test-input.txt").unwrap();
+    let (_, mut monkeys) = parse_input(&input).unwrap();
+    assert_eq!(
+        Troop::try_from(monkeys.clone()).unwrap().modulus,
+        23 * 19 * 13 * 17
+    );
+
+    monkeys[0].test.modulo = 6;
+    monkeys[1].test.modulo = 4;
+    monkeys[2].test.modulo = 10;
+    monkeys[3].test.modulo = 3;
+    assert_eq!(Troop::try_from(monkeys).unwrap().modulus, 60);
+}
+
+#[test]
+fn troop_rejects_bad_references() {
+    let input = read_to_string("./src/test-input.txt").unwrap();
+    let (_, monkeys) = parse_input(&input).unwrap();
+
+    let mut dangling = monkeys.clone();
+    dangling[1].test.if_false = 4;
+    assert_eq!(
+        Troop::try_from(dangling),
+        Err(TroopError::DanglingReference {
+            monkey: 1,
+            target: 4
+        })
+    );
+
+    let mut self_throw = monkeys.clone();
+    self_throw[3].test.if_true = 3;
+    assert_eq!(
+        Troop::try_from(self_throw),
+        Err(TroopError::SelfThrow { monkey: 3 })
+    );
+
+    let mut zero = monkeys;
+    zero[2].test.modulo = 0;
+    assert_eq!(
+        Troop::try_from(zero),
+        Err(TroopError::ZeroDivisor { monkey: 2 })
+    );
+
+    // Simulating a bad troop is an error rather than a panic
+    let self_throw = input.replace("If false: throw to monkey 1", "If false: throw to monkey 3");
+    assert_eq!(
+        simulate(&self_throw, SimulationConfig::PART1, None),
+        Err(TroopError::SelfThrow { monkey: 3 }.to_string())
+    );
+    assert!(simulate("Monkey 0:", SimulationConfig::PART1, None).is_err());
+}
+
+#[test]
+fn troop_rejects_overflowing_worry_levels() {
+    let input = read_to_string("./src/test-input.txt").unwrap();
+    let (_, monkeys) = parse_input(&input).unwrap();
+
+    // Monkey 2 squares, which only fits in a `u128` below 2^64
+    let mut square = monkeys.clone();
+    square[0].test.modulo = (1 << 64) + 1;
+    assert_eq!(
+        Troop::try_from(square),
+        Err(TroopError::WorryOverflow { monkey: 2 })
+    );
+
+    let mut large = monkeys.clone();
+    large[0].test.modulo = (1 << 40) - 87;
+    large[1].test.modulo = (1 << 40) - 167;
+    assert_eq!(
+        Troop::try_from(large),
+        Err(TroopError::WorryOverflow { monkey: 2 })
+    );
+
+    // The least common multiple itself doesn't fit
+    let mut lcm = monkeys.clone();
+    lcm[0].test.modulo = u128::MAX;
+    lcm[1].test.modulo = 2;
+    assert_eq!(Troop::try_from(lcm), Err(TroopError::ModulusOverflow));
+
+    // Big starting items count too, even with a small modulus
+    let mut item = monkeys.clone();
+    item[3].items.push_back(Item(u128::MAX - 1));
+    assert_eq!(
+        Troop::try_from(item),
+        Err(TroopError::WorryOverflow { monkey: 0 })
+    );
+
+    // Right at the limit is fine
+    let mut limit = monkeys;
+    limit
+        .iter_mut()
+        .for_each(|monkey| monkey.test.modulo = 1 << 64);
+    assert_eq!(Troop::try_from(limit).unwrap().modulus, 1 << 64);
+}
+
+#[test]
+fn simulation_config() {
+    let input = read_to_string("./src/test-input.txt").unwrap();
+    let args: Vec<String> = ["simulate", "--rounds", "20", "--top", "3"]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+    let config = SimulationConfig::from_args(&args).unwrap();
+
+    assert_eq!(config.relief, 3);
+    assert_eq!(simulate(&input, config, None), Ok(105 * 101 * 95));
+
+    let bad_relief: Vec<String> = vec![String::from("--relief"), String::from("0")];
+    assert!(SimulationConfig::from_args(&bad_relief).is_err());
 
-    while &monkeys[n].items.len() > &0 {
-        let item = monkeys[n].items.pop().unwrap();
-        let recipient = monkeys[n].test(&item);
-        monkeys[recipient].items.push(item);
+    let bad_top: Vec<String> = vec![String::from("--top"), String::from("0")];
+    assert!(SimulationConfig::from_args(&bad_top).is_err());
+}
+
+#[test]
+fn relief_keeps_exact_worry_levels() {
+    let input = read_to_string("./src/test-input.txt").unwrap();
+    let (_, monkeys) = parse_input(&input).unwrap();
+
+    // The straightforward simulation, without any modulus
+    let exact = |relief: u128, rounds: usize| -> Vec<u128> {
+        let mut items: Vec<Vec<u128>> = monkeys
+            .iter()
+            .map(|monkey| monkey.items.iter().map(|item| item.0).collect())
+            .collect();
+        let mut activity = vec![0; monkeys.len()];
+
+        for _ in 0..rounds {
+            for (idx, monkey) in monkeys.iter().enumerate() {
+                for old in std::mem::take(&mut items[idx]) {
+                    activity[idx] += 1;
+                    let new = monkey.worry_update.apply(old).unwrap() / relief;
+                    items[monkey.test(&Item(new))].push(new);
+                }
+            }
+        }
+
+        activity
+    };
+
+    // Reducing modulo the LCM before dividing goes wrong after round 9 with a
+    // relief of 2, and after round 28 with a relief of 3
+    for (relief, rounds) in [(2, 20), (3, 30), (3, 100)] {
+        let mut troop = Troop::try_from(monkeys.clone()).unwrap();
+        let config = SimulationConfig {
+            rounds,
+            relief,
+            top: 2,
+        };
+        troop.simulate(config, None).unwrap();
+
+        let activity: Vec<u128> = troop.monkeys.iter().map(|monkey| monkey.activity).collect();
+        assert_eq!(activity, exact(relief, rounds), "relief {relief}");
     }
+
+    // Exact worry levels don't fit in a `u128` for long
+    let mut troop = Troop::try_from(monkeys).unwrap();
+    let config = SimulationConfig {
+        rounds: 30,
+        relief: 2,
+        top: 2,
+    };
+    assert_eq!(
+        troop.simulate(config, None),
+        Err(TroopError::WorryOverflow { monkey: 2 })
+    );
 }
 
-fn main() {
-    let input = read_to_string("./src/input.txt").unwrap();
-    println!("The solution to part 1 is: {}", part1(&input));
-    println!("The solution to part 2 is: {}", part2(&input));
+#[test]
+fn items_are_thrown_in_order() {
+    let input = read_to_string("./src/test-input.txt").unwrap();
+    let (_, monkeys) = parse_input(&input).unwrap();
+    let mut troop = Troop::try_from(monkeys).unwrap();
+
+    let levels = |troop: &Troop| -> Vec<Vec<u128>> {
+        troop
+            .monkeys
+            .iter()
+            .map(|monkey| monkey.items.iter().map(|item| item.0).collect())
+            .collect()
+    };
+
+    assert_eq!(
+        levels(&troop),
+        vec![
+            vec![79, 98],
+            vec![54, 65, 75, 74],
+            vec![79, 60, 97],
+            vec![74]
+        ]
+    );
+
+    troop.play_round(3, None).unwrap();
+    assert_eq!(
+        levels(&troop),
+        vec![
+            vec![20, 23, 27, 26],
+            vec![2080, 25, 167, 207, 401, 1046],
+            vec![],
+            vec![]
+        ]
+    );
+
+    troop.play_round(3, None).unwrap();
+    assert_eq!(
+        levels(&troop),
+        vec![
+            vec![695, 10, 71, 135, 350],
+            vec![43, 49, 58, 55, 362],
+            vec![],
+            vec![]
+        ]
+    );
 }
 
 #[test]
-fn part1_works() {
+fn batch_simulates_every_input() {
+    let dir = env::temp_dir().join(format!("day11-batch-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
     let input = read_to_string("./src/test-input.txt").unwrap();
-    assert_eq!(part1(&input), 10605);
+    std::fs::write(dir.join("a.txt"), &input).unwrap();
+    std::fs::write(dir.join("b.txt"), &input).unwrap();
+    std::fs::write(dir.join("c.txt"), input.replace("monkey 3", "monkey 7")).unwrap();
+
+    // Squaring worry levels modulo ~2^80 overflows, which has to be
+    // reported for this file rather than take down the whole batch
+    std::fs::write(
+        dir.join("d.txt"),
+        "Monkey 0:
+  Starting items: 1099511627689
+  Operation: new = old * old
+  Test: divisible by 1099511627689
+    If true: throw to monkey 1
+    If false: throw to monkey 1
+
+Monkey 1:
+  Starting items: 1099511627609
+  Operation: new = old * old
+  Test: divisible by 1099511627609
+    If true: throw to monkey 0
+    If false: throw to monkey 0
+",
+    )
+    .unwrap();
+
+    let results = batch::run(&dir, SimulationConfig::PART1).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let values: Vec<_> = results
+        .into_iter()
+        .map(|result| result.monkey_business)
+        .collect();
+
+    assert_eq!(values[0], Ok(10605));
+    assert_eq!(values[1], Ok(10605));
+    assert!(values[2].is_err());
+    assert_eq!(
+        values[3],
+        Err(TroopError::WorryOverflow { monkey: 0 }.to_string())
+    );
 }
 
 #[test]
-fn part2_works() {
+fn report_renders_a_round() {
     let input = read_to_string("./src/test-input.txt").unwrap();
-    assert_eq!(part2(&input), 2713310158);
+    let config = SimulationConfig {
+        rounds: 1,
+        ..SimulationConfig::PART1
+    };
+    let mut history = History::default();
+    simulate(&input, config, Some(&mut history)).unwrap();
+
+    // Matches the first round of the puzzle's walkthrough
+    let csv = report::render(&history, report::Format::Csv);
+    assert_eq!(
+        csv,
+        "# Inspections per round
+round,monkey 0,monkey 1,monkey 2,monkey 3
+1,2,4,3,5
+
+# Items thrown (from \\ to)
+from,monkey 0,monkey 1,monkey 2,monkey 3
+monkey 0,0,0,0,2
+monkey 1,4,0,0,0
+monkey 2,0,1,0,2
+monkey 3,0,5,0,0
+
+# Thrown items per worry level
+round,0,2^0..2^1,2^1..2^2,2^2..2^3,2^3..2^4,2^4..2^5,2^5..2^6,2^6..2^7,2^7..2^8,2^8..2^9,2^9..2^10,2^10..2^11,2^11..2^12
+1,0,0,0,0,0,5,0,0,2,2,1,2,2
+"
+    );
+
+    let table = report::render(&history, report::Format::Table);
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines[0], "Inspections per round");
+    assert_eq!(
+        lines[1],
+        "round | monkey 0 | monkey 1 | monkey 2 | monkey 3"
+    );
+    assert_eq!(
+        lines[2],
+        "------+----------+----------+----------+---------"
+    );
+    assert_eq!(
+        lines[3],
+        "    1 |        2 |        4 |        3 |        5"
+    );
+    assert_eq!(lines[5], "Items thrown (from \\ to)");
+    assert_eq!(
+        lines[8],
+        "monkey 0 |        0 |        0 |        0 |        2"
+    );
+    assert_eq!(
+        lines[11],
+        "monkey 3 |        0 |        5 |        0 |        0"
+    );
+    assert_eq!(lines[13], "Thrown items per worry level");
+    assert!(lines[14].starts_with("round | 0 | 2^0..2^1 |"));
+    assert!(lines[14].ends_with("| 2^10..2^11 | 2^11..2^12"));
+    assert!(lines[16].ends_with("|        2 |        2 |         1 |          2 |          2"));
+    assert_eq!(lines.len(), 17);
 }
@@ -0,0 +1,170 @@
+/*
+ * Rendering the recorded history as a set of tables
+ */
+
+use crate::History;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Table,
+    Csv,
+}
+
+struct Table {
+    title: String,
+    header: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    fn render(&self, format: Format) -> String {
+        match format {
+            Format::Csv => self.render_csv(),
+            Format::Table => self.render_table(),
+        }
+    }
+
+    fn render_csv(&self) -> String {
+        let mut out = format!("# {}\n", self.title);
+
+        for row in std::iter::once(&self.header).chain(self.rows.iter()) {
+            out.push_str(&row.join(","));
+            out.push('\n');
+        }
+
+        out
+    }
+
+    fn render_table(&self) -> String {
+        // Every column is as wide as its widest cell
+        let widths: Vec<usize> = (0..self.header.len())
+            .map(|col| {
+                std::iter::once(&self.header)
+                    .chain(self.rows.iter())
+                    .map(|row| row[col].len())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+
+        let line = |row: &Vec<String>| {
+            row.iter()
+                .zip(widths.iter())
+                .map(|(cell, width)| format!("{cell:>width$}"))
+                .collect::<Vec<_>>()
+                .join(" | ")
+        };
+
+        let mut out = format!("{}\n", self.title);
+        out.push_str(&line(&self.header));
+        out.push('\n');
+        out.push_str(
+            &widths
+                .iter()
+                .map(|width| "-".repeat(*width))
+                .collect::<Vec<_>>()
+                .join("-+-"),
+        );
+        out.push('\n');
+
+        for row in self.rows.iter() {
+            out.push_str(&line(row));
+            out.push('\n');
+        }
+
+        out
+    }
+}
+
+fn monkey_header(first: &str, monkeys: usize) -> Vec<String> {
+    std::iter::once(first.to_string())
+        .chain((0..monkeys).map(|monkey| format!("monkey {monkey}")))
+        .collect()
+}
+
+fn inspections(history: &History) -> Table {
+    let monkeys = history
+        .rounds
+        .first()
+        .map_or(0, |round| round.inspections.len());
+
+    Table {
+        title: String::from("Inspections per round"),
+        header: monkey_header("round", monkeys),
+        rows: history
+            .rounds
+            .iter()
+            .enumerate()
+            .map(|(round, stats)| {
+                std::iter::once((round + 1).to_string())
+                    .chain(stats.inspections.iter().map(u128::to_string))
+                    .collect()
+            })
+            .collect(),
+    }
+}
+
+fn transfers(history: &History) -> Table {
+    let matrix = history.transfer_matrix();
+
+    Table {
+        title: String::from("Items thrown (from \\ to)"),
+        header: monkey_header("from", matrix.len()),
+        rows: matrix
+            .iter()
+            .enumerate()
+            .map(|(from, row)| {
+                std::iter::once(format!("monkey {from}"))
+                    .chain(row.iter().map(u128::to_string))
+                    .collect()
+            })
+            .collect(),
+    }
+}
+
+/// The worry levels in a bucket, as a half-open range
+fn bucket_label(bucket: usize) -> String {
+    match bucket {
+        0 => String::from("0"),
+        _ => format!("2^{}..2^{bucket}", bucket - 1),
+    }
+}
+
+fn worry(history: &History) -> Table {
+    let buckets = history
+        .rounds
+        .iter()
+        .map(|round| round.worry.len())
+        .max()
+        .unwrap_or(0);
+
+    Table {
+        title: String::from("Thrown items per worry level"),
+        header: std::iter::once(String::from("round"))
+            .chain((0..buckets).map(bucket_label))
+            .collect(),
+        rows: history
+            .rounds
+            .iter()
+            .enumerate()
+            .map(|(round, stats)| {
+                std::iter::once((round + 1).to_string())
+                    .chain(
+                        (0..buckets).map(|bucket| {
+                            stats.worry.get(bucket).copied().unwrap_or(0).to_string()
+                        }),
+                    )
+                    .collect()
+            })
+            .collect(),
+    }
+}
+
+/// Render the inspection counts, transfer matrix and worry histogram
+pub fn render(history: &History, format: Format) -> String {
+    [inspections(history), transfers(history), worry(history)]
+        .iter()
+        .map(|table| table.render(format))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,29 @@
+use std::{fs::read_to_string, hint::black_box};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use day3::hashset;
+
+fn itemset(c: &mut Criterion) {
+    let input = read_to_string("./src/input.txt").expect("Failed to read input");
+
+    let mut group = c.benchmark_group("part1");
+    group.bench_function("itemset", |b| {
+        b.iter(|| day3::part1(&day3::parse_input(black_box(&input)).unwrap()))
+    });
+    group.bench_function("hashset", |b| {
+        b.iter(|| hashset::part1(&hashset::parse_input(black_box(&input))))
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("part2");
+    group.bench_function("itemset", |b| {
+        b.iter(|| day3::part2(&day3::parse_input(black_box(&input)).unwrap()))
+    });
+    group.bench_function("hashset", |b| {
+        b.iter(|| hashset::part2(&hashset::parse_input(black_box(&input))))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, itemset);
+criterion_main!(benches);
@@ -0,0 +1,74 @@
+//! The original `HashSet` based solution, kept around to benchmark `ItemSet`
+//! against
+
+use itertools::Itertools;
+use std::collections::HashSet;
+
+use crate::Item;
+
+////////////////////////////////////////////////////////////////////////////////
+// Data definitions
+////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone)]
+pub struct Backpack {
+    pub left: HashSet<Item>,
+    pub right: HashSet<Item>,
+}
+
+impl Backpack {
+    pub fn all(&self) -> HashSet<Item> {
+        &self.left | &self.right
+    }
+
+    pub fn common_object(&self) -> Option<Item> {
+        let common = &self.left & &self.right;
+
+        common.into_iter().next()
+    }
+}
+
+impl From<&str> for Backpack {
+    fn from(input: &str) -> Backpack {
+        let compartment_size: usize = input.len() / 2;
+
+        let left: HashSet<Item> = input[0..compartment_size]
+            .chars()
+            .map(|c| Item::try_from(c).expect("Invalid item"))
+            .collect();
+
+        let right: HashSet<Item> = input[compartment_size..(compartment_size * 2)]
+            .chars()
+            .map(|c| Item::try_from(c).expect("Invalid item"))
+            .collect();
+
+        Backpack { left, right }
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Parsing
+////////////////////////////////////////////////////////////////////////////////
+pub fn parse_input(input: &str) -> Vec<Backpack> {
+    input.lines().map(Backpack::from).collect()
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Solutions
+////////////////////////////////////////////////////////////////////////////////
+pub fn part1(input: &[Backpack]) -> u32 {
+    input
+        .iter()
+        .map(|backpack| backpack.common_object().expect("No item in common"))
+        .map(|item| item.priority())
+        .sum()
+}
+
+pub fn part2(input: &[Backpack]) -> u32 {
+    input
+        .iter()
+        .tuples::<(_, _, _)>()
+        .map(|(one, two, three)| &(&one.all() & &two.all()) & &three.all())
+        .map(|intersection| intersection.into_iter().next().expect("No item in common"))
+        .map(|item| item.priority())
+        .sum()
+}
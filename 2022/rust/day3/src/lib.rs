@@ -0,0 +1,444 @@
+pub mod hashset;
+
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::{BitAnd, BitOr},
+};
+
+////////////////////////////////////////////////////////////////////////////////
+// Data definitions
+////////////////////////////////////////////////////////////////////////////////
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BackpackError {
+    /// The items can't be split evenly between the two compartments
+    OddLength(usize),
+    /// Items are ASCII letters
+    InvalidItem(char),
+    /// Both compartments, or all three backpacks in a group, should have
+    /// exactly one item in common
+    CommonItems(Vec<Item>),
+    /// Backpacks come in groups of three, but the last group has fewer
+    IncompleteGroup(usize),
+}
+
+impl Display for BackpackError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BackpackError::OddLength(len) => {
+                write!(f, "{len} items can't be split between two compartments")
+            }
+            BackpackError::InvalidItem(c) => write!(f, "{c:?} isn't a valid item"),
+            BackpackError::CommonItems(items) if items.is_empty() => {
+                write!(f, "No item in common")
+            }
+            BackpackError::CommonItems(items) => {
+                let items: String = items.iter().map(|item| item.item).collect();
+                write!(f, "More than one item in common: {items}")
+            }
+            BackpackError::IncompleteGroup(len) => {
+                write!(f, "Group of {len} backpacks, instead of three")
+            }
+        }
+    }
+}
+
+/// A `BackpackError` along with the (1-based) line it's on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub error: BackpackError,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.error)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub struct Item {
+    item: char,
+}
+
+impl Item {
+    /// The letter on the item. Items can only be made from ASCII letters,
+    /// through `TryFrom<char>`.
+    pub fn as_char(&self) -> char {
+        self.item
+    }
+
+    pub fn priority(&self) -> u32 {
+        if self.item.is_ascii_lowercase() {
+            u32::from(self.item) - 96
+        } else {
+            u32::from(self.item) - 38
+        }
+    }
+
+    fn from_priority(priority: u32) -> Item {
+        let item = if priority <= 26 {
+            b'a' + (priority - 1) as u8
+        } else {
+            b'A' + (priority - 27) as u8
+        };
+
+        Item { item: item as char }
+    }
+}
+
+impl TryFrom<char> for Item {
+    type Error = BackpackError;
+
+    fn try_from(c: char) -> Result<Item, Self::Error> {
+        if c.is_ascii_alphabetic() {
+            Ok(Item { item: c })
+        } else {
+            Err(BackpackError::InvalidItem(c))
+        }
+    }
+}
+
+/// A set of items, with one bit per priority. Since there are only 52 of
+/// them, the whole set fits in a `u64`.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, Default)]
+pub struct ItemSet(u64);
+
+impl ItemSet {
+    pub fn new() -> ItemSet {
+        ItemSet(0)
+    }
+
+    pub fn insert(&mut self, item: Item) {
+        self.0 |= 1 << item.priority();
+    }
+
+    pub fn contains(&self, item: Item) -> bool {
+        self.0 & (1 << item.priority()) != 0
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Every item in the set, in order of priority
+    pub fn iter(&self) -> impl Iterator<Item = Item> {
+        let mut bits = self.0;
+
+        std::iter::from_fn(move || {
+            if bits == 0 {
+                return None;
+            }
+
+            let priority = bits.trailing_zeros();
+            // Clear the lowest set bit
+            bits &= bits - 1;
+
+            Some(Item::from_priority(priority))
+        })
+    }
+}
+
+impl BitAnd for ItemSet {
+    type Output = ItemSet;
+
+    fn bitand(self, other: ItemSet) -> ItemSet {
+        ItemSet(self.0 & other.0)
+    }
+}
+
+impl BitOr for ItemSet {
+    type Output = ItemSet;
+
+    fn bitor(self, other: ItemSet) -> ItemSet {
+        ItemSet(self.0 | other.0)
+    }
+}
+
+impl FromIterator<Item> for ItemSet {
+    fn from_iter<I: IntoIterator<Item = Item>>(items: I) -> ItemSet {
+        let mut set = ItemSet::new();
+        items.into_iter().for_each(|item| set.insert(item));
+        set
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Backpack {
+    pub left: ItemSet,
+    pub right: ItemSet,
+    common: Item,
+}
+
+impl Backpack {
+    pub fn all(&self) -> ItemSet {
+        self.left | self.right
+    }
+
+    pub fn common_object(&self) -> Item {
+        self.common
+    }
+}
+
+/// The one item in `set`, if there's exactly one
+fn single_item(set: ItemSet) -> Result<Item, BackpackError> {
+    match set.len() {
+        1 => Ok(set.iter().next().unwrap()),
+        _ => Err(BackpackError::CommonItems(set.iter().collect())),
+    }
+}
+
+impl TryFrom<&str> for Backpack {
+    type Error = BackpackError;
+
+    fn try_from(input: &str) -> Result<Backpack, Self::Error> {
+        let items = input
+            .chars()
+            .map(Item::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if items.len() % 2 != 0 {
+            return Err(BackpackError::OddLength(items.len()));
+        }
+
+        let (left, right) = items.split_at(items.len() / 2);
+        let left: ItemSet = left.iter().copied().collect();
+        let right: ItemSet = right.iter().copied().collect();
+        let common = single_item(left & right)?;
+
+        Ok(Backpack {
+            left,
+            right,
+            common,
+        })
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Parsing
+////////////////////////////////////////////////////////////////////////////////
+pub fn parse_input(input: &str) -> Result<Vec<Backpack>, ParseError> {
+    input
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| {
+            Backpack::try_from(line).map_err(|error| ParseError {
+                line: idx + 1,
+                error,
+            })
+        })
+        .collect()
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Solutions
+////////////////////////////////////////////////////////////////////////////////
+pub fn part1(input: &[Backpack]) -> u32 {
+    input
+        .iter()
+        .map(|backpack| backpack.common_object())
+        // Convert into numerical value
+        .map(|item| item.priority())
+        .sum()
+}
+
+pub fn part2(input: &[Backpack]) -> Result<u32, ParseError> {
+    input
+        .chunks(3)
+        .enumerate()
+        .map(|(idx, group)| {
+            let line = idx * 3 + 1;
+
+            let [one, two, three] = group else {
+                return Err(ParseError {
+                    line,
+                    error: BackpackError::IncompleteGroup(group.len()),
+                });
+            };
+
+            // Find common intersection using bitwise and on the sets
+            let intersection = one.all() & two.all() & three.all();
+
+            // Unwrap the item from the set, and convert it to a numerical value
+            single_item(intersection)
+                .map(|item| item.priority())
+                .map_err(|error| ParseError { line, error })
+        })
+        .sum()
+}
+
+////////////////////////////////////////////////////////////////////////////////
+// Tests
+////////////////////////////////////////////////////////////////////////////////
+#[cfg(test)]
+use std::fs::read_to_string;
+
+#[cfg(test)]
+fn item(c: char) -> Item {
+    Item::try_from(c).unwrap()
+}
+
+#[test]
+fn priority() {
+    assert_eq!(
+        Item::try_from('a').unwrap().priority(),
+        1,
+        "'a' has priority 1"
+    );
+    assert_eq!(
+        Item::try_from('z').unwrap().priority(),
+        26,
+        "'z' has priority 26"
+    );
+    assert_eq!(
+        Item::try_from('A').unwrap().priority(),
+        27,
+        "'A' has priority 27"
+    );
+    assert_eq!(
+        Item::try_from('E').unwrap().priority(),
+        31,
+        "'E' has priority 31"
+    );
+}
+
+#[test]
+fn backpack_from_str() {
+    let backpack = Backpack::try_from("abCa").unwrap();
+
+    assert!(backpack.left.contains(item('a')));
+    assert!(backpack.left.contains(item('b')));
+    assert!(backpack.right.contains(item('C')));
+    assert!(backpack.right.contains(item('a')));
+
+    assert!(!backpack.right.contains(item('b')));
+    assert_eq!(backpack.common_object(), item('a'));
+}
+
+#[test]
+fn item_set() {
+    let one: ItemSet = "abcXYZ".chars().map(item).collect();
+    let two: ItemSet = "cdeZ".chars().map(item).collect();
+
+    assert_eq!(one.len(), 6);
+    assert!(ItemSet::new().is_empty());
+
+    let common: Vec<char> = (one & two).iter().map(|item| item.as_char()).collect();
+    assert_eq!(common, vec!['c', 'Z']);
+
+    let all: String = (one | two).iter().map(|item| item.as_char()).collect();
+    assert_eq!(all, "abcdeXYZ");
+
+    // Every item makes it back out of the set unchanged
+    let everything: ItemSet = ('a'..='z').chain('A'..='Z').map(item).collect();
+    assert_eq!(everything.len(), 52);
+    assert!(everything
+        .iter()
+        .map(|item| item.as_char())
+        .eq(('a'..='z').chain('A'..='Z')));
+}
+
+#[test]
+fn part1_works() {
+    let input = read_to_string("./src/test-input.txt").expect("Failed to read input");
+    let input = parse_input(&input).unwrap();
+    assert_eq!(part1(&input), 157);
+}
+
+#[test]
+fn part2_works() {
+    let input = read_to_string("./src/test-input.txt").expect("Failed to read input");
+    let input = parse_input(&input).unwrap();
+    assert_eq!(part2(&input), Ok(70));
+}
+
+#[test]
+fn matches_hashset() {
+    let input = read_to_string("./src/input.txt").expect("Failed to read input");
+    assert_eq!(
+        part1(&parse_input(&input).unwrap()),
+        hashset::part1(&hashset::parse_input(&input))
+    );
+    assert_eq!(
+        part2(&parse_input(&input).unwrap()),
+        Ok(hashset::part2(&hashset::parse_input(&input)))
+    );
+}
+
+#[test]
+fn malformed_backpacks() {
+    let error = |line: usize, error: BackpackError| Err(ParseError { line, error });
+
+    // Anything that isn't an ASCII letter has no priority
+    for c in ['1', '~', ' ', 'é', '@', '['] {
+        assert_eq!(
+            Item::try_from(c).unwrap_err(),
+            BackpackError::InvalidItem(c)
+        );
+    }
+
+    // The middle item used to be silently dropped
+    assert_eq!(
+        parse_input("abcAa\n").unwrap_err(),
+        ParseError {
+            line: 1,
+            error: BackpackError::OddLength(5)
+        }
+    );
+    assert_eq!(
+        parse_input("aa\nab-b\n").unwrap_err(),
+        ParseError {
+            line: 2,
+            error: BackpackError::InvalidItem('-')
+        }
+    );
+    assert_eq!(
+        parse_input("aa\nbb\néé\n").unwrap_err().error,
+        BackpackError::InvalidItem('é')
+    );
+    assert_eq!(
+        parse_input("aa\nbb\ncc\nabcd\n").unwrap_err(),
+        ParseError {
+            line: 4,
+            error: BackpackError::CommonItems(vec![])
+        }
+    );
+    assert_eq!(
+        parse_input("abBAba").unwrap_err(),
+        ParseError {
+            line: 1,
+            error: BackpackError::CommonItems(vec![item('a'), item('b')])
+        }
+    );
+
+    // Badges
+    let backpacks = parse_input("xaxb\nxcxd\nxexf\nZaZb\nZcZd\n").unwrap();
+    assert_eq!(
+        part2(&backpacks),
+        error(4, BackpackError::IncompleteGroup(2))
+    );
+
+    let backpacks = parse_input("xyax\nxycx\nxydy\n").unwrap();
+    assert_eq!(
+        part2(&backpacks),
+        error(1, BackpackError::CommonItems(vec![item('x'), item('y')]))
+    );
+
+    let backpacks = parse_input("aa\nbb\ncc\n").unwrap();
+    assert_eq!(
+        part2(&backpacks),
+        error(1, BackpackError::CommonItems(vec![]))
+    );
+
+    assert_eq!(
+        ParseError {
+            line: 3,
+            error: BackpackError::CommonItems(vec![item('a'), item('B')])
+        }
+        .to_string(),
+        "Line 3: More than one item in common: aB"
+    );
+}
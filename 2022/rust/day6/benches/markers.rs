@@ -0,0 +1,52 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use day6::{find_unique_sequence, simd};
+
+const SIZE: usize = 100 * 1024 * 1024;
+
+/// `len` random letters out of the first `n - 1`, so there's never a marker
+/// of length `n` and every implementation has to read the whole input
+fn random_input(len: usize, n: usize) -> String {
+    // xorshift64, which is plenty random for this
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (b'a' + (state % (n as u64 - 1)) as u8) as char
+        })
+        .collect()
+}
+
+fn markers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("markers");
+    group.sample_size(10);
+    group.throughput(Throughput::Bytes(SIZE as u64));
+
+    for n in [4, 8, 14] {
+        let input = random_input(SIZE, n);
+
+        group.bench_with_input(BenchmarkId::new("scalar", n), &input, |b, input| {
+            b.iter(|| find_unique_sequence(black_box(input), n))
+        });
+
+        for strategy in [simd::Strategy::Bitmask, simd::Strategy::Avx2] {
+            if !strategy.is_supported(n) {
+                continue;
+            }
+
+            let name = format!("{strategy:?}").to_lowercase();
+            group.bench_with_input(BenchmarkId::new(name, n), &input, |b, input| {
+                b.iter(|| simd::find_unique_sequence(black_box(input.as_bytes()), n, strategy))
+            });
+        }
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, markers);
+criterion_main!(benches);
@@ -0,0 +1,88 @@
+pub mod markers;
+pub mod simd;
+
+use std::io::{self, Read};
+
+use markers::{markers, Alphabet, ByteCounts, Window};
+
+/// The number of bytes read up to and including the first run of `n`
+/// distinct bytes, if there is one
+pub fn find_unique_sequence(input: &str, n: usize) -> Option<usize> {
+    markers(input, n, Alphabet::Bytes).next()
+}
+
+/// Same as `find_unique_sequence`, but reading from `reader` a chunk at a
+/// time. Only the last `n` bytes are kept around.
+pub fn find_unique_sequence_in(mut reader: impl Read, n: usize) -> io::Result<Option<usize>> {
+    let mut window = Window::<ByteCounts>::new();
+    let mut recent = vec![0; n];
+    let mut chunk = vec![0; 64 * 1024];
+    let mut position = 0;
+
+    if n == 0 {
+        return Ok(Some(0));
+    }
+
+    loop {
+        let read = match reader.read(&mut chunk) {
+            Ok(0) => return Ok(None),
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        for &byte in &chunk[..read] {
+            // `recent` is a ring buffer, so the byte leaving the window sits
+            // exactly where the new one goes
+            let slot = position % n;
+
+            if position >= n {
+                window.pop(recent[slot]);
+            }
+
+            window.push(byte);
+            recent[slot] = byte;
+            position += 1;
+
+            if position >= n && window.is_unique() {
+                return Ok(Some(position));
+            }
+        }
+    }
+}
+
+/// Same as `find_unique_sequence`, using the fastest implementation the CPU
+/// supports
+pub fn find_unique_sequence_fast(input: &str, n: usize) -> Option<usize> {
+    simd::find_unique_sequence(input.as_bytes(), n, simd::Strategy::detect(n))
+}
+
+/*
+ * Tests
+ */
+#[test]
+fn streaming_matches() {
+    let examples = [
+        "mjqjpqmgbljsphdztnvjfqwrcgsmlb",
+        "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg",
+        "abcabcabcabc",
+        "",
+    ];
+
+    for input in examples {
+        for n in [1, 4, 14] {
+            assert_eq!(
+                find_unique_sequence_in(input.as_bytes(), n).unwrap(),
+                find_unique_sequence(input, n)
+            );
+        }
+    }
+
+    // A marker that straddles two chunks
+    let input = "ab".repeat(64 * 1024) + "cd";
+    assert_eq!(find_unique_sequence(&input, 4), Some(128 * 1024 + 2));
+    assert_eq!(
+        find_unique_sequence_in(input.as_bytes(), 4).unwrap(),
+        Some(128 * 1024 + 2)
+    );
+}
@@ -1,45 +1,94 @@
-use std::{collections::HashSet, fs::read_to_string};
+use std::{
+    env,
+    fs::{read_to_string, File},
+};
 
-/*
- * Parsing
- */
-fn parse_input(input: &str) -> Vec<char> {
-    input.chars().collect()
-}
+use day6::{
+    find_unique_sequence, find_unique_sequence_in,
+    markers::{markers, Alphabet},
+};
 
 /*
 * Solutions
 */
 fn main() {
+    // `day6 markers <length> [--unicode]` lists every marker in the input
+    if env::args().nth(1).as_deref() == Some("markers") {
+        let input = read_to_string("./src/input.txt").unwrap();
+        let n = env::args()
+            .nth(2)
+            .map_or(14, |n| n.parse().expect("Invalid marker length"));
+        let alphabet = if env::args().any(|arg| arg == "--unicode") {
+            Alphabet::Unicode
+        } else {
+            Alphabet::Bytes
+        };
+
+        for position in markers(&input, n, alphabet) {
+            println!("{position}");
+        }
+
+        return;
+    }
+
+    // `day6 <path> [length]` scans a capture of any size without loading it
+    // into memory first
+    if let Some(path) = env::args().nth(1) {
+        let n = env::args()
+            .nth(2)
+            .map_or(14, |n| n.parse().expect("Invalid marker length"));
+        let file = File::open(&path).expect("Failed to open capture");
+
+        match find_unique_sequence_in(file, n).expect("Failed to read capture") {
+            Some(position) => println!("The first marker ends at {position}"),
+            None => println!("There is no marker of length {n}"),
+        }
+
+        return;
+    }
+
     let input = read_to_string("./src/input.txt").unwrap();
-    println!("The solution to part 1 is {}", part1(&input));
-    println!("The solution to part 2 is {}", part2(&input));
+    println!("The solution to part 1 is {}", part1(&input).unwrap());
+    println!("The solution to part 2 is {}", part2(&input).unwrap());
 }
 
-fn part1(input: &str) -> usize {
+fn part1(input: &str) -> Option<usize> {
     find_unique_sequence(input, 4)
 }
 
-fn part2(input: &str) -> usize {
+fn part2(input: &str) -> Option<usize> {
     find_unique_sequence(input, 14)
 }
 
-fn find_unique_sequence(input: &str, n: usize) -> usize {
-    let chars = parse_input(input);
-
-    &chars
-        .windows(n)
-        .position(|window| window.iter().collect::<HashSet<_>>().len() == n)
-        .unwrap()
-        + n
-}
-
 /*
  * Tests
  */
 #[test]
 fn part1_works() {
-    assert_eq!(part1("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), 7);
-    assert_eq!(part1("bvwbjplbgvbhsrlpgdmjqwftvncz"), 5);
-    assert_eq!(part1("nppdvjthqldpwncqszvftbrmjlhg"), 6);
+    assert_eq!(part1("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), Some(7));
+    assert_eq!(part1("bvwbjplbgvbhsrlpgdmjqwftvncz"), Some(5));
+    assert_eq!(part1("nppdvjthqldpwncqszvftbrmjlhg"), Some(6));
+    assert_eq!(part1("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"), Some(10));
+    assert_eq!(part1("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"), Some(11));
+}
+
+#[test]
+fn part2_works() {
+    assert_eq!(part2("mjqjpqmgbljsphdztnvjfqwrcgsmlb"), Some(19));
+    assert_eq!(part2("bvwbjplbgvbhsrlpgdmjqwftvncz"), Some(23));
+    assert_eq!(part2("nppdvjthqldpwncqszvftbrmjlhg"), Some(23));
+    assert_eq!(part2("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg"), Some(29));
+    assert_eq!(part2("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw"), Some(26));
+    assert_eq!(
+        part2(&read_to_string("./src/input.txt").unwrap()),
+        Some(2421)
+    );
+}
+
+#[test]
+fn no_marker() {
+    assert_eq!(part1(""), None);
+    assert_eq!(part1("abc"), None);
+    assert_eq!(part1("abcabcabcabc"), None);
+    assert_eq!(part2("abcdefghijklm"), None);
 }
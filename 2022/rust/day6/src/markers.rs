@@ -0,0 +1,227 @@
+/*
+ * Finding every marker, in bytes or in Unicode scalar values
+ */
+
+use std::collections::{HashMap, VecDeque};
+
+/// What counts as a single character of the signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alphabet {
+    /// Every byte on its own. Fast, but a multi-byte character counts as
+    /// several, and positions are byte offsets.
+    Bytes,
+    /// Every `char`, with positions counted in `char`s
+    Unicode,
+}
+
+/// How many times every item appears in a window
+pub trait Counts {
+    type Item: Copy;
+
+    fn new() -> Self;
+    fn count(&mut self, item: Self::Item) -> &mut usize;
+}
+
+/// A flat table, since there are only 256 possible bytes
+pub struct ByteCounts([usize; 256]);
+
+impl Counts for ByteCounts {
+    type Item = u8;
+
+    fn new() -> Self {
+        ByteCounts([0; 256])
+    }
+
+    fn count(&mut self, item: u8) -> &mut usize {
+        &mut self.0[item as usize]
+    }
+}
+
+impl Counts for HashMap<char, usize> {
+    type Item = char;
+
+    fn new() -> Self {
+        HashMap::new()
+    }
+
+    fn count(&mut self, item: char) -> &mut usize {
+        self.entry(item).or_insert(0)
+    }
+}
+
+/// The counts of every item in the current window, and how many items appear
+/// more than once
+pub struct Window<C> {
+    counts: C,
+    duplicates: usize,
+}
+
+impl<C: Counts> Window<C> {
+    pub fn new() -> Window<C> {
+        Window {
+            counts: C::new(),
+            duplicates: 0,
+        }
+    }
+
+    pub fn push(&mut self, item: C::Item) {
+        let count = self.counts.count(item);
+        *count += 1;
+
+        if *count == 2 {
+            self.duplicates += 1;
+        }
+    }
+
+    pub fn pop(&mut self, item: C::Item) {
+        let count = self.counts.count(item);
+
+        if *count == 2 {
+            self.duplicates -= 1;
+        }
+
+        *count -= 1;
+    }
+
+    pub fn is_unique(&self) -> bool {
+        self.duplicates == 0
+    }
+}
+
+impl<C: Counts> Default for Window<C> {
+    fn default() -> Self {
+        Window::new()
+    }
+}
+
+/// Every position right after a run of `n` distinct items
+pub struct Markers<I: Iterator, C> {
+    items: I,
+    recent: VecDeque<I::Item>,
+    window: Window<C>,
+    n: usize,
+    position: usize,
+    /// Whether we've already looked at the window ending at `position`
+    checked: bool,
+}
+
+impl<I: Iterator, C: Counts<Item = I::Item>> Markers<I, C> {
+    pub fn new(items: I, n: usize) -> Markers<I, C> {
+        Markers {
+            items,
+            recent: VecDeque::with_capacity(n + 1),
+            window: Window::new(),
+            n,
+            position: 0,
+            checked: false,
+        }
+    }
+}
+
+impl<I: Iterator, C: Counts<Item = I::Item>> Iterator for Markers<I, C>
+where
+    I::Item: Copy,
+{
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            if !self.checked && self.recent.len() == self.n && self.window.is_unique() {
+                self.checked = true;
+                return Some(self.position);
+            }
+
+            let item = self.items.next()?;
+            self.window.push(item);
+            self.recent.push_back(item);
+
+            if self.recent.len() > self.n {
+                let oldest = self.recent.pop_front().unwrap();
+                self.window.pop(oldest);
+            }
+
+            self.position += 1;
+            self.checked = false;
+        }
+    }
+}
+
+/// Every marker of length `n` in `input`, in order
+pub fn markers(input: &str, n: usize, alphabet: Alphabet) -> Box<dyn Iterator<Item = usize> + '_> {
+    match alphabet {
+        Alphabet::Bytes => Box::new(Markers::<_, ByteCounts>::new(input.bytes(), n)),
+        Alphabet::Unicode => Box::new(Markers::<_, HashMap<char, usize>>::new(input.chars(), n)),
+    }
+}
+
+/*
+ * Tests
+ */
+#[test]
+fn all_markers() {
+    use std::collections::HashSet;
+
+    // Checking every window by hand
+    let naive = |input: &str, n: usize| -> Vec<usize> {
+        let chars: Vec<char> = input.chars().collect();
+
+        (n..=chars.len())
+            .filter(|&end| chars[end - n..end].iter().collect::<HashSet<_>>().len() == n)
+            .collect()
+    };
+
+    for input in [
+        "mjqjpqmgbljsphdztnvjfqwrcgsmlb",
+        "nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg",
+        "zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw",
+    ] {
+        for n in [1, 4, 14] {
+            let all: Vec<usize> = markers(input, n, Alphabet::Bytes).collect();
+            assert_eq!(all, naive(input, n));
+            assert_eq!(
+                all,
+                markers(input, n, Alphabet::Unicode).collect::<Vec<_>>()
+            );
+            assert_eq!(all.first().copied(), crate::find_unique_sequence(input, n));
+        }
+    }
+
+    assert_eq!(
+        markers("abc", 0, Alphabet::Bytes).collect::<Vec<_>>(),
+        vec![0, 1, 2, 3]
+    );
+    assert_eq!(markers("aaaa", 2, Alphabet::Bytes).count(), 0);
+}
+
+#[test]
+fn markers_outside_a_to_z() {
+    // Capitals, digits and punctuation are just as good as letters
+    let input = "AAB1!x";
+    assert_eq!(
+        markers(input, 4, Alphabet::Bytes).collect::<Vec<_>>(),
+        vec![5, 6]
+    );
+    assert_eq!(
+        markers(input, 4, Alphabet::Unicode).collect::<Vec<_>>(),
+        vec![5, 6]
+    );
+
+    // Every `é` is two bytes, which are different from each other
+    let input = "ééé";
+    assert_eq!(
+        markers(input, 2, Alphabet::Bytes).collect::<Vec<_>>(),
+        vec![2, 3, 4, 5, 6]
+    );
+    assert_eq!(markers(input, 2, Alphabet::Unicode).count(), 0);
+
+    // Positions are counted in `char`s, not bytes
+    let input = "🎄🎅🎄🎁⭐";
+    assert_eq!(
+        markers(input, 3, Alphabet::Unicode).collect::<Vec<_>>(),
+        vec![4, 5]
+    );
+    assert_eq!(
+        markers("δαβγα δ", 4, Alphabet::Unicode).collect::<Vec<_>>(),
+        vec![4, 6, 7]
+    );
+}
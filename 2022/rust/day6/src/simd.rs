@@ -0,0 +1,182 @@
+/*
+ * Checking many windows at once
+ */
+
+/// How `find_unique_sequence` goes about it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Check a window back to front with a 256-bit mask of the bytes seen so
+    /// far. A repeat rules out every window that contains both copies, so we
+    /// can skip straight past the first one.
+    Bitmask,
+    /// Compare 32 bytes at a time against each of the `n - 1` bytes before
+    /// them, to find how far back every byte was last seen
+    Avx2,
+}
+
+impl Strategy {
+    /// Whether this CPU can run the strategy for markers of length `n`
+    pub fn is_supported(&self, n: usize) -> bool {
+        match self {
+            Strategy::Bitmask => true,
+            // Distances have to fit in a byte
+            #[cfg(target_arch = "x86_64")]
+            Strategy::Avx2 => n < 256 && is_x86_feature_detected!("avx2"),
+            #[cfg(not(target_arch = "x86_64"))]
+            Strategy::Avx2 => false,
+        }
+    }
+
+    /// The fastest strategy this CPU supports for markers of length `n`.
+    /// Checking every window, even 32 at a time, only beats skipping ahead
+    /// for really short markers (see `benches/markers.rs`).
+    pub fn detect(n: usize) -> Strategy {
+        if n <= 4 && Strategy::Avx2.is_supported(n) {
+            Strategy::Avx2
+        } else {
+            Strategy::Bitmask
+        }
+    }
+}
+
+/// The number of bytes read up to and including the first run of `n`
+/// distinct bytes, if there is one. Falls back to `Strategy::Bitmask` if the
+/// CPU doesn't support `strategy`.
+pub fn find_unique_sequence(bytes: &[u8], n: usize, strategy: Strategy) -> Option<usize> {
+    match strategy {
+        #[cfg(target_arch = "x86_64")]
+        Strategy::Avx2 if strategy.is_supported(n) => {
+            // SAFETY: we just checked that the CPU supports AVX2
+            unsafe { avx2::find_unique_sequence(bytes, n) }
+        }
+        _ => bitmask(bytes, n),
+    }
+}
+
+fn bitmask(bytes: &[u8], n: usize) -> Option<usize> {
+    let mut start = 0;
+
+    'windows: while start + n <= bytes.len() {
+        let mut seen = [0u64; 4];
+
+        for idx in (start..start + n).rev() {
+            let byte = bytes[idx];
+            let (word, bit) = ((byte >> 6) as usize, 1 << (byte & 63));
+
+            if seen[word] & bit != 0 {
+                start = idx + 1;
+                continue 'windows;
+            }
+
+            seen[word] |= bit;
+        }
+
+        return Some(start + n);
+    }
+
+    None
+}
+
+#[cfg(target_arch = "x86_64")]
+mod avx2 {
+    use std::arch::x86_64::*;
+
+    const LANES: usize = 32;
+
+    /// How far back the closest copy of the byte at `idx` is, or 0 if there
+    /// isn't one in the previous `n - 1` bytes
+    fn distance(bytes: &[u8], idx: usize, n: usize) -> u8 {
+        (1..n.min(idx + 1))
+            .find(|&back| bytes[idx - back] == bytes[idx])
+            .unwrap_or(0) as u8
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn find_unique_sequence(bytes: &[u8], n: usize) -> Option<usize> {
+        if n == 0 {
+            return Some(0);
+        }
+
+        let mut distances = [0u8; LANES];
+        // Where the first window without a repeat could start
+        let mut earliest = 0;
+        let mut idx = 0;
+
+        while idx < bytes.len() {
+            let block = LANES.min(bytes.len() - idx);
+
+            if block == LANES && idx + 1 >= n {
+                let current = _mm256_loadu_si256(bytes.as_ptr().add(idx) as *const __m256i);
+                let mut found = _mm256_setzero_si256();
+
+                // From furthest to closest, so the closest copy wins
+                for back in (1..n).rev() {
+                    let previous =
+                        _mm256_loadu_si256(bytes.as_ptr().add(idx - back) as *const __m256i);
+                    let equal = _mm256_cmpeq_epi8(current, previous);
+                    found = _mm256_blendv_epi8(found, _mm256_set1_epi8(back as u8 as i8), equal);
+                }
+
+                _mm256_storeu_si256(distances.as_mut_ptr() as *mut __m256i, found);
+            } else {
+                // The start and end of the input, where we can't load a
+                // full vector
+                for (offset, slot) in distances[..block].iter_mut().enumerate() {
+                    *slot = distance(bytes, idx + offset, n);
+                }
+            }
+
+            for (offset, &back) in distances[..block].iter().enumerate() {
+                let position = idx + offset;
+
+                if back != 0 {
+                    earliest = earliest.max(position - back as usize + 1);
+                }
+
+                if position + 1 - earliest >= n {
+                    return Some(position + 1);
+                }
+            }
+
+            idx += block;
+        }
+
+        None
+    }
+}
+
+/*
+ * Tests
+ */
+#[test]
+fn strategies_agree() {
+    // Random-ish letters from a small alphabet, so markers are neither
+    // everywhere nor nowhere
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    let input: String = (0..10_000)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (b'a' + (state % 16) as u8) as char
+        })
+        .collect();
+
+    for n in [0, 1, 2, 4, 8, 14, 16, 17] {
+        for len in [0, 1, 13, 31, 32, 33, 100, 1000, 10_000] {
+            let input = &input[..len];
+            let expected = crate::find_unique_sequence(input, n);
+
+            for strategy in [Strategy::Bitmask, Strategy::Avx2] {
+                assert_eq!(
+                    find_unique_sequence(input.as_bytes(), n, strategy),
+                    expected,
+                    "{strategy:?} with n = {n} on {len} bytes"
+                );
+            }
+        }
+    }
+
+    let input = std::fs::read_to_string("./src/input.txt").unwrap();
+    assert_eq!(crate::find_unique_sequence_fast(&input, 14), Some(2421));
+}
@@ -0,0 +1,111 @@
+/*
+ * Line-of-sight queries from anywhere in the forest
+ */
+
+use std::collections::HashSet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Direction {
+    pub const CARDINAL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    pub const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+        Direction::UpLeft,
+        Direction::UpRight,
+        Direction::DownLeft,
+        Direction::DownRight,
+    ];
+
+    /// Change in (row, column) for a single step
+    fn delta(&self) -> (isize, isize) {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+            Direction::UpLeft => (-1, -1),
+            Direction::UpRight => (-1, 1),
+            Direction::DownLeft => (1, -1),
+            Direction::DownRight => (1, 1),
+        }
+    }
+}
+
+/// A (row, column) position in the grid
+pub type Cell = (usize, usize);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Forest {
+    trees: Vec<Vec<u32>>,
+}
+
+impl Forest {
+    pub fn new(trees: Vec<Vec<u32>>) -> Forest {
+        Forest { trees }
+    }
+
+    /// The height of the tree at `cell`, if it's inside the forest
+    pub fn height_at(&self, (row, col): Cell) -> Option<u32> {
+        self.trees.get(row)?.get(col).copied()
+    }
+
+    /// The trees an observer at `from`, with their eyes at `height`, sees when
+    /// looking in `direction`, nearest first. Just like for the scenic score,
+    /// the view stops at (and includes) the first tree that's at least as
+    /// tall as the observer.
+    pub fn ray(&self, from: Cell, direction: Direction, height: u32) -> Vec<Cell> {
+        let (dr, dc) = direction.delta();
+        let step =
+            |(row, col): Cell| Some((row.checked_add_signed(dr)?, col.checked_add_signed(dc)?));
+        let mut visible = Vec::new();
+        let mut cell = from;
+
+        while let Some((next, tree)) =
+            step(cell).and_then(|next| Some((next, self.height_at(next)?)))
+        {
+            visible.push(next);
+            cell = next;
+
+            if tree >= height {
+                break;
+            }
+        }
+
+        visible
+    }
+
+    /// Every tree visible from `from` along any of the given directions
+    pub fn visible_from(&self, from: Cell, height: u32, directions: &[Direction]) -> HashSet<Cell> {
+        directions
+            .iter()
+            .flat_map(|&direction| self.ray(from, direction, height))
+            .collect()
+    }
+
+    /// Whether an observer at `from` can see the tree at `target`, looking
+    /// along one of the given directions
+    pub fn can_see(&self, from: Cell, height: u32, target: Cell, directions: &[Direction]) -> bool {
+        directions
+            .iter()
+            .any(|&direction| self.ray(from, direction, height).contains(&target))
+    }
+}
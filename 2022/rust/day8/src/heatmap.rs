@@ -0,0 +1,137 @@
+/*
+ * Rendering the forest as a heatmap
+ */
+
+use crate::{get_distances, get_visibilities};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Which edges a tree is visible from
+    Visibility,
+    /// How a tree's scenic score compares to the best one
+    Score,
+}
+
+type Rgb = (u8, u8, u8);
+
+/// Blend from a dark blue (0.0) to a bright yellow (1.0)
+fn gradient(t: f64) -> Rgb {
+    let (from, to) = ((0x1e, 0x1e, 0x4e), (0xf9, 0xe2, 0x2f));
+    let blend = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+
+    (
+        blend(from.0, to.0),
+        blend(from.1, to.1),
+        blend(from.2, to.2),
+    )
+}
+
+/// The colour for a tree visible from the given edges. Every edge adds to
+/// one channel of the dark blue a hidden tree gets: the top a lot of red, the
+/// bottom a little, the left a lot of green and the right a little. That way
+/// every combination of edges gets its own colour, and a tree visible from
+/// everywhere ends up yellow.
+pub fn edge_colour(up: bool, down: bool, left: bool, right: bool) -> Rgb {
+    let (base, strong, weak) = (0x1e, 0x82, 0x4f);
+    let channel = |strong_edge: bool, weak_edge: bool| {
+        base + if strong_edge { strong } else { 0 } + if weak_edge { weak } else { 0 }
+    };
+
+    (channel(up, down), channel(left, right), 0x4e)
+}
+
+/// Swatches for every edge on its own, and for all of them together
+pub fn legend() -> String {
+    [
+        ("top", edge_colour(true, false, false, false)),
+        ("bottom", edge_colour(false, true, false, false)),
+        ("left", edge_colour(false, false, true, false)),
+        ("right", edge_colour(false, false, false, true)),
+        ("all", edge_colour(true, true, true, true)),
+        ("none", edge_colour(false, false, false, false)),
+    ]
+    .iter()
+    .map(|(name, (r, g, b))| format!("\x1b[48;2;{r};{g};{b}m  \x1b[0m {name}"))
+    .collect::<Vec<_>>()
+    .join("  ")
+}
+
+fn visibility_colours(trees: &[Vec<u32>]) -> Vec<Vec<Rgb>> {
+    get_visibilities(trees.to_vec())
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|tree| edge_colour(tree.up, tree.down, tree.left, tree.right))
+                .collect()
+        })
+        .collect()
+}
+
+fn score_colours(trees: &[Vec<u32>]) -> Vec<Vec<Rgb>> {
+    let scores: Vec<Vec<u32>> = get_distances(trees.to_vec())
+        .iter()
+        .map(|row| row.iter().map(|distances| distances.score()).collect())
+        .collect();
+
+    let max = scores.iter().flatten().copied().max().unwrap_or(0).max(1);
+
+    // Scores are products of four distances, so only a handful of trees come
+    // anywhere near the best one. Taking the square root keeps the rest from
+    // all ending up the same shade of dark.
+    scores
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|&score| gradient((score as f64 / max as f64).sqrt()))
+                .collect()
+        })
+        .collect()
+}
+
+/// The colour of every tree in the grid
+pub fn colours(trees: &[Vec<u32>], mode: Mode) -> Vec<Vec<Rgb>> {
+    match mode {
+        Mode::Visibility => visibility_colours(trees),
+        Mode::Score => score_colours(trees),
+    }
+}
+
+/// Every tree's height, on a coloured background, for a terminal that
+/// supports 24-bit colour
+pub fn render_ansi(trees: &[Vec<u32>], mode: Mode) -> String {
+    colours(trees, mode)
+        .iter()
+        .zip(trees.iter())
+        .map(|(colours, heights)| {
+            let line: String = colours
+                .iter()
+                .zip(heights.iter())
+                .map(|((r, g, b), height)| format!("\x1b[48;2;{r};{g};{b}m{height}"))
+                .collect();
+
+            format!("{line}\x1b[0m")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A binary PPM image, where every tree is a `scale` × `scale` square
+pub fn render_ppm(trees: &[Vec<u32>], mode: Mode, scale: usize) -> Vec<u8> {
+    let colours = colours(trees, mode);
+    let height = colours.len();
+    let width = colours.first().map_or(0, |row| row.len());
+
+    let mut out = format!("P6\n{} {}\n255\n", width * scale, height * scale).into_bytes();
+
+    for row in colours.iter() {
+        for _ in 0..scale {
+            for &(r, g, b) in row.iter() {
+                for _ in 0..scale {
+                    out.extend([r, g, b]);
+                }
+            }
+        }
+    }
+
+    out
+}
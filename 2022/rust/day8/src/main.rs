@@ -2,9 +2,12 @@
  * Data structures
  */
 
-use std::fs::read_to_string;
+mod forest;
+mod heatmap;
 
-#[derive(Debug)]
+use std::{env, fs::read_to_string};
+
+#[derive(Debug, PartialEq, Eq)]
 struct TreeVisibility {
     height: u32,
     up: bool,
@@ -25,7 +28,7 @@ impl TreeVisibility {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Eq)]
 struct TreeDistances {
     up: u32,
     down: u32,
@@ -60,6 +63,99 @@ fn parse_input(input: &str) -> Vec<Vec<u32>> {
 
 fn main() {
     let input = read_to_string("./src/input.txt").unwrap();
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    // `heatmap <visibility|score> [--ppm <path>]`
+    if args.first().map(String::as_str) == Some("heatmap") {
+        let usage = || -> ! {
+            eprintln!("Usage: day8 heatmap <visibility|score> [--ppm <path>]");
+            std::process::exit(1);
+        };
+        let mode = match args.get(1).map(String::as_str) {
+            Some("visibility") => heatmap::Mode::Visibility,
+            Some("score") => heatmap::Mode::Score,
+            _ => usage(),
+        };
+
+        let trees = parse_input(&input);
+
+        match &args[2..] {
+            [flag, path] if flag == "--ppm" => {
+                std::fs::write(path, heatmap::render_ppm(&trees, mode, 4))
+                    .expect("Failed to write image")
+            }
+            [] => {
+                println!("{}", heatmap::render_ansi(&trees, mode));
+
+                if mode == heatmap::Mode::Visibility {
+                    println!("\n{}", heatmap::legend());
+                }
+            }
+            _ => usage(),
+        }
+
+        return;
+    }
+
+    // `sight <row> <col> [--height <h>] [--diagonal] [--to <row>,<col>]`,
+    // where the height defaults to that of the tree the observer is on
+    if args.first().map(String::as_str) == Some("sight") {
+        let usage = || -> ! {
+            eprintln!(
+                "Usage: day8 sight <row> <col> [--height <h>] [--diagonal] [--to <row>,<col>]"
+            );
+            std::process::exit(1);
+        };
+        let number = |idx: usize| -> usize {
+            args.get(idx)
+                .and_then(|arg| arg.parse().ok())
+                .unwrap_or_else(|| usage())
+        };
+
+        let (row, col) = (number(1), number(2));
+        let flag = |name: &str| {
+            args.iter()
+                .position(|arg| arg == name)
+                .map(|idx| args.get(idx + 1))
+        };
+        let forest = forest::Forest::new(parse_input(&input));
+
+        let height = match flag("--height") {
+            Some(height) => height
+                .and_then(|height| height.parse().ok())
+                .unwrap_or_else(|| usage()),
+            None => forest.height_at((row, col)).unwrap_or_else(|| {
+                eprintln!("({row}, {col}) is outside the forest");
+                std::process::exit(1);
+            }),
+        };
+        let directions: &[forest::Direction] = match flag("--diagonal") {
+            Some(_) => &forest::Direction::ALL,
+            None => &forest::Direction::CARDINAL,
+        };
+
+        match flag("--to") {
+            Some(target) => {
+                let (r, c) = target
+                    .and_then(|target| target.split_once(','))
+                    .and_then(|(r, c)| Some((r.parse().ok()?, c.parse().ok()?)))
+                    .unwrap_or_else(|| usage());
+                let verb = if forest.can_see((row, col), height, (r, c), directions) {
+                    "can"
+                } else {
+                    "can't"
+                };
+                println!("({row}, {col}) {verb} see ({r}, {c})");
+            }
+            None => {
+                let visible = forest.visible_from((row, col), height, directions);
+                println!("{} trees are visible from ({row}, {col})", visible.len());
+            }
+        }
+
+        return;
+    }
+
     println!("The solution to part 1 is: {}", part1(&input));
     println!("The solution to part 2 is: {}", part2(&input));
 }
@@ -83,7 +179,7 @@ fn part2(input: &str) -> u32 {
         .flat_map(|row| row.iter())
         .map(|distances| distances.score())
         .max()
-        .unwrap()
+        .unwrap_or(0)
 }
 
 fn get_visibilities(trees: Vec<Vec<u32>>) -> Vec<Vec<TreeVisibility>> {
@@ -92,50 +188,116 @@ fn get_visibilities(trees: Vec<Vec<u32>>) -> Vec<Vec<TreeVisibility>> {
         .map(|trees| trees.into_iter().map(TreeVisibility::new).collect())
         .collect();
 
-    // Set top-down and left-to-right visibilities
-    let mut left_right_max: Vec<u32> = vec![0; visibilities.len()];
-    let mut top_bottom_max: Vec<u32> = vec![0; visibilities[0].len()];
+    let cols = visibilities.first().map_or(0, |row| row.len());
+
+    // The tallest tree seen so far when coming in from an edge, if any. A tree
+    // is visible if it's taller than that, so trees on the edge (with nothing
+    // in front of them) are always visible, even if they have height 0.
+    let visible = |height: u32, max: &mut Option<u32>| {
+        let visible = max.is_none_or(|max| height > max);
+        *max = (*max).max(Some(height));
+        visible
+    };
+
+    // Set left-to-right and right-to-left visibilities
+    for row in visibilities.iter_mut() {
+        let mut max = None;
+        for tree in row.iter_mut() {
+            tree.left = visible(tree.height, &mut max);
+        }
 
-    for (i, row) in visibilities.iter_mut().enumerate() {
-        for (j, tree) in row.iter_mut().enumerate() {
-            tree.left = j == 0 || tree.height > left_right_max[i];
-            tree.up = i == 0 || tree.height > top_bottom_max[j];
+        let mut max = None;
+        for tree in row.iter_mut().rev() {
+            tree.right = visible(tree.height, &mut max);
+        }
+    }
 
-            if tree.height > left_right_max[i] {
-                left_right_max[i] = tree.height;
-            }
+    // Set top-down and bottom-to-top visibilities
+    for j in 0..cols {
+        let mut max = None;
+        for row in visibilities.iter_mut() {
+            row[j].up = visible(row[j].height, &mut max);
+        }
 
-            if tree.height > top_bottom_max[j] {
-                top_bottom_max[j] = tree.height;
-            }
+        let mut max = None;
+        for row in visibilities.iter_mut().rev() {
+            row[j].down = visible(row[j].height, &mut max);
         }
     }
 
-    // Set right-to-left and bottom-to-top visibilities
-    let mut right_left_max: Vec<u32> = vec![0u32; visibilities.len()];
-    let mut bottom_top_max: Vec<u32> = vec![0u32; visibilities[0].len()];
-    for (i, row) in visibilities.iter_mut().rev().enumerate() {
-        for (j, tree) in row.iter_mut().rev().enumerate() {
-            tree.right = i == 0 || tree.height > right_left_max[i];
-            tree.down = j == 0 || tree.height > bottom_top_max[j];
+    visibilities
+}
 
-            if tree.height > right_left_max[i] {
-                right_left_max[i] = tree.height;
+/// For every tree in a line, how many trees we can see when looking back
+/// towards the start of the line.
+///
+/// We keep a stack of the trees that could still block the view of trees
+/// further down the line, in decreasing order of height. A tree hides every
+/// shorter tree behind it, so those can be popped off for good, and the tree
+/// left on top of the stack is the one blocking our view. That way every tree
+/// gets pushed and popped at most once.
+fn look_back(heights: &[u32]) -> Vec<u32> {
+    let mut stack: Vec<usize> = Vec::new();
+
+    heights
+        .iter()
+        .enumerate()
+        .map(|(i, height)| {
+            while stack.last().is_some_and(|&j| heights[j] < *height) {
+                stack.pop();
             }
 
-            if tree.height > bottom_top_max[j] {
-                bottom_top_max[j] = tree.height;
-            }
+            // If nothing blocks the view, we can see all the way to the edge
+            let distance = i - stack.last().copied().unwrap_or(0);
+            stack.push(i);
+
+            distance as u32
+        })
+        .collect()
+}
+
+fn get_distances(trees: Vec<Vec<u32>>) -> Vec<Vec<TreeDistances>> {
+    let mut distances: Vec<Vec<TreeDistances>> = trees
+        .iter()
+        .map(|trees| trees.iter().map(|_| TreeDistances::new()).collect())
+        .collect();
+
+    for (i, row) in trees.iter().enumerate() {
+        let reversed: Vec<u32> = row.iter().rev().copied().collect();
+
+        for (j, distance) in look_back(row).into_iter().enumerate() {
+            distances[i][j].left = distance;
+        }
+
+        for (j, distance) in look_back(&reversed).into_iter().rev().enumerate() {
+            distances[i][j].right = distance;
         }
     }
 
-    visibilities
+    for j in 0..trees.first().map_or(0, |row| row.len()) {
+        let column: Vec<u32> = trees.iter().map(|row| row[j]).collect();
+        let reversed: Vec<u32> = column.iter().rev().copied().collect();
+
+        for (i, distance) in look_back(&column).into_iter().enumerate() {
+            distances[i][j].up = distance;
+        }
+
+        for (i, distance) in look_back(&reversed).into_iter().rev().enumerate() {
+            distances[i][j].down = distance;
+        }
+    }
+
+    distances
 }
 
-fn get_distances(trees: Vec<Vec<u32>>) -> Vec<Vec<TreeDistances>> {
+/// Straightforward version of `get_distances` that walks outward from every
+/// tree. We only keep it around to check the fast one against.
+#[cfg(test)]
+#[allow(clippy::needless_range_loop)]
+fn get_distances_naive(trees: Vec<Vec<u32>>) -> Vec<Vec<TreeDistances>> {
     let mut distances: Vec<Vec<TreeDistances>> = trees
         .iter()
-        .map(|trees| trees.into_iter().map(|_| TreeDistances::new()).collect())
+        .map(|trees| trees.iter().map(|_| TreeDistances::new()).collect())
         .collect();
 
     for (i, row) in trees.iter().enumerate() {
@@ -178,7 +340,7 @@ fn get_distances(trees: Vec<Vec<u32>>) -> Vec<Vec<TreeDistances>> {
         }
     }
 
-    dbg!(distances)
+    distances
 }
 
 #[test]
@@ -192,3 +354,185 @@ fn part2_works() {
     let input = read_to_string("./src/test-input.txt").unwrap();
     assert_eq!(part2(&input), 8);
 }
+
+#[test]
+fn distances_match_naive_implementation() {
+    let mut grids = vec![
+        parse_input(&read_to_string("./src/test-input.txt").unwrap()),
+        parse_input(&read_to_string("./src/input.txt").unwrap()),
+        parse_input("0"),
+        parse_input("1203\n3021"),
+    ];
+
+    // A few pseudo-random rectangular grids, with lots of equal heights
+    let mut seed: u32 = 8;
+    for (rows, cols) in [(7, 13), (13, 7), (1, 20), (20, 1)] {
+        grids.push(
+            (0..rows)
+                .map(|_| {
+                    (0..cols)
+                        .map(|_| {
+                            seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+                            (seed >> 16) % 4
+                        })
+                        .collect()
+                })
+                .collect(),
+        );
+    }
+
+    for trees in grids {
+        assert_eq!(get_distances(trees.clone()), get_distances_naive(trees));
+    }
+}
+
+#[test]
+fn heatmaps() {
+    let trees = parse_input(&read_to_string("./src/test-input.txt").unwrap());
+
+    // Corners are visible from two edges, the hidden center from none
+    let colours = heatmap::colours(&trees, heatmap::Mode::Visibility);
+    assert_eq!(
+        colours[0][0],
+        heatmap::edge_colour(true, false, true, false)
+    );
+    assert_eq!(
+        colours[4][4],
+        heatmap::edge_colour(false, true, false, true)
+    );
+    assert_ne!(colours[0][0], colours[4][4]);
+    assert_eq!(
+        colours[2][2],
+        heatmap::colours(&trees, heatmap::Mode::Score)[0][0]
+    );
+
+    // Every combination of edges can be told apart
+    let combinations: std::collections::HashSet<_> = (0..16)
+        .map(|edges: u32| {
+            heatmap::edge_colour(
+                edges & 1 != 0,
+                edges & 2 != 0,
+                edges & 4 != 0,
+                edges & 8 != 0,
+            )
+        })
+        .collect();
+    assert_eq!(combinations.len(), 16);
+
+    // The tree with the best scenic score gets the brightest colour
+    let colours = heatmap::colours(&trees, heatmap::Mode::Score);
+    assert_eq!(colours[3][2], (0xf9, 0xe2, 0x2f));
+
+    let ansi = heatmap::render_ansi(&trees, heatmap::Mode::Score);
+    assert_eq!(ansi.lines().count(), 5);
+    assert_eq!(ansi.matches("\x1b[48;2;").count(), 25);
+
+    let ppm = heatmap::render_ppm(&trees, heatmap::Mode::Visibility, 2);
+    assert!(ppm.starts_with(b"P6\n10 10\n255\n"));
+    assert_eq!(ppm.len(), "P6\n10 10\n255\n".len() + 10 * 10 * 3);
+}
+
+#[test]
+fn visibility_in_rectangular_grids() {
+    // Every tree in a grid with only two rows or columns is on an edge
+    assert_eq!(part1("30373\n25512"), 10);
+    assert_eq!(part1("12\n34\n56"), 6);
+
+    let visibilities = get_visibilities(parse_input("0000\n0100\n0000"));
+    assert_eq!(
+        visibilities
+            .iter()
+            .flatten()
+            .filter(|tree| tree.up || tree.down || tree.left || tree.right)
+            .count(),
+        11
+    );
+
+    // Top-right corner: only visible from the edges it sits on, since the
+    // height-0 trees in front of it block the view from the other edges
+    let corner = &visibilities[0][3];
+    assert!(corner.up && corner.right && !corner.left && !corner.down);
+
+    // An interior tree of height 0 is hidden from every direction
+    let hidden = &visibilities[1][2];
+    assert!(!hidden.up && !hidden.down && !hidden.left && !hidden.right);
+
+    let tallest = &visibilities[1][1];
+    assert!(tallest.up && tallest.down && tallest.left && tallest.right);
+
+    // Transposing the grid transposes the visibilities
+    let transposed = get_visibilities(parse_input("000\n010\n000\n000"));
+    assert!(transposed[1][1].left && transposed[1][1].down);
+    assert_eq!(
+        transposed[3][0],
+        TreeVisibility {
+            height: 0,
+            up: false,
+            down: true,
+            left: true,
+            right: false,
+        }
+    );
+}
+
+#[test]
+fn empty_grid() {
+    assert_eq!(part1(""), 0);
+    assert_eq!(part2(""), 0);
+}
+
+#[test]
+fn line_of_sight() {
+    use forest::{Direction, Forest};
+
+    let trees = parse_input(&read_to_string("./src/test-input.txt").unwrap());
+    let forest = Forest::new(trees.clone());
+
+    // Standing on top of a tree, the rays are exactly the viewing distances
+    for (i, row) in get_distances(trees.clone()).iter().enumerate() {
+        for (j, distances) in row.iter().enumerate() {
+            let height = trees[i][j];
+            assert_eq!(
+                forest.ray((i, j), Direction::Up, height).len() as u32,
+                distances.up
+            );
+            assert_eq!(
+                forest.ray((i, j), Direction::Down, height).len() as u32,
+                distances.down
+            );
+            assert_eq!(
+                forest.ray((i, j), Direction::Left, height).len() as u32,
+                distances.left
+            );
+            assert_eq!(
+                forest.ray((i, j), Direction::Right, height).len() as u32,
+                distances.right
+            );
+        }
+    }
+
+    // From the tree with the best scenic score, in the middle of the 4th row
+    assert_eq!(forest.ray((3, 2), Direction::Up, 5), vec![(2, 2), (1, 2)]);
+
+    let visible = forest.visible_from((3, 2), 5, &Direction::CARDINAL);
+    assert_eq!(visible.len(), 2 + 2 + 1 + 2);
+
+    // Looking diagonally too
+    let visible = forest.visible_from((3, 2), 5, &Direction::ALL);
+    assert_eq!(visible.len(), 7 + 1 + 2 + 1 + 1);
+    assert!(forest.can_see((3, 2), 5, (1, 4), &Direction::ALL));
+    assert!(!forest.can_see((3, 2), 5, (1, 4), &Direction::CARDINAL));
+
+    // Nothing to see past the first tree that's too tall
+    assert!(!forest.can_see((3, 2), 5, (0, 2), &Direction::CARDINAL));
+
+    // A really tall observer sees all the way to the edges
+    let visible = forest.visible_from((2, 2), 10, &Direction::ALL);
+    assert_eq!(visible.len(), 16);
+
+    // There's no tree to stand on outside the forest
+    assert_eq!(forest.height_at((3, 2)), Some(5));
+    assert_eq!(forest.height_at((4, 4)), Some(0));
+    assert_eq!(forest.height_at((5, 0)), None);
+    assert_eq!(forest.height_at((0, 5)), None);
+}
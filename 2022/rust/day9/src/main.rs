@@ -1,65 +1,116 @@
-use itertools::Itertools;
-use std::{collections::HashSet, fs::read_to_string};
+mod visualise;
 
+use std::{
+    collections::HashSet,
+    env,
+    fs::read_to_string,
+    fs::File,
+    io::{self, BufWriter},
+};
+
+/// A position in `N`-dimensional space. The axes are x (`L`/`R`), y (`D`/`U`)
+/// and z (`B`/`F`), in that order.
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
-struct Position {
-    x: i32,
-    y: i32,
+struct Position<const N: usize = 2> {
+    coords: [i32; N],
+}
+
+impl<const N: usize> Default for Position<N> {
+    fn default() -> Self {
+        Position { coords: [0; N] }
+    }
 }
 
 impl Position {
+    fn new(x: i32, y: i32) -> Position {
+        Position { coords: [x, y] }
+    }
+
+    fn x(&self) -> i32 {
+        self.coords[0]
+    }
+
+    fn y(&self) -> i32 {
+        self.coords[1]
+    }
+}
+
+impl<const N: usize> Position<N> {
     fn adjacent(&self, other: &Self) -> bool {
-        i32::max((self.x - other.x).abs(), (self.y - other.y).abs()) <= 1
+        self.coords
+            .iter()
+            .zip(other.coords.iter())
+            .all(|(a, b)| (a - b).abs() <= 1)
     }
 
     fn approach(&mut self, other: &Self) {
         if !self.adjacent(other) {
-            self.x -= (self.x - other.x).signum();
-            self.y -= (self.y - other.y).signum();
+            for (a, b) in self.coords.iter_mut().zip(other.coords.iter()) {
+                *a -= (*a - b).signum();
+            }
         }
     }
 
-    fn go(&mut self, instruction: &Instruction) {
-        match instruction {
-            Instruction::Up => self.y += 1,
-            Instruction::Down => self.y -= 1,
-            Instruction::Left => self.x -= 1,
-            Instruction::Right => self.x += 1,
-        };
+    fn go(&mut self, instruction: &Instruction<N>) {
+        for (coord, delta) in self.coords.iter_mut().zip(instruction.delta.iter()) {
+            *coord += delta;
+        }
     }
 }
 
-impl Default for Position {
-    fn default() -> Self {
-        Position { x: 0, y: 0 }
-    }
+/// A single step of the head, which moves every coordinate by at most one.
+/// Letters can be combined to move diagonally, e.g., `UL` or `DRF`.
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
+struct Instruction<const N: usize = 2> {
+    delta: [i32; N],
 }
 
+/// A number of steps in the same direction, e.g., `R 4`
 #[derive(Debug, PartialEq, Eq, Hash, Copy, Clone)]
-enum Instruction {
-    Up,
-    Down,
-    Left,
-    Right,
+struct Motion<const N: usize = 2> {
+    direction: Instruction<N>,
+    count: usize,
 }
 
-impl TryFrom<&str> for Instruction {
+impl<const N: usize> TryFrom<&str> for Instruction<N> {
     type Error = &'static str;
-    fn try_from(value: &str) -> Result<Instruction, Self::Error> {
-        match value {
-            "U" => Ok(Instruction::Up),
-            "D" => Ok(Instruction::Down),
-            "L" => Ok(Instruction::Left),
-            "R" => Ok(Instruction::Right),
-            _ => Err("Could not parse instruction!"),
+    fn try_from(value: &str) -> Result<Instruction<N>, Self::Error> {
+        let mut delta = [0; N];
+
+        if value.is_empty() {
+            return Err("Could not parse instruction!");
         }
+
+        for c in value.chars() {
+            let (axis, step) = match c {
+                'L' => (0, -1),
+                'R' => (0, 1),
+                'D' => (1, -1),
+                'U' => (1, 1),
+                'B' => (2, -1),
+                'F' => (2, 1),
+                _ => return Err("Could not parse instruction!"),
+            };
+
+            if axis >= N {
+                return Err("Instruction moves along an axis we don't have!");
+            }
+
+            if delta[axis] != 0 {
+                return Err("Instruction moves along the same axis twice!");
+            }
+
+            delta[axis] = step;
+        }
+
+        Ok(Instruction { delta })
     }
 }
 
-fn parse_input(input: &str) -> Vec<Instruction> {
+fn parse_input<const N: usize>(input: &str) -> Vec<Motion<N>> {
     input
         .lines()
-        .flat_map(|line| {
+        .map(|line| {
             let mut iter = line.split(" ");
             let direction = iter.next().map(Instruction::try_from).unwrap().unwrap();
             let count = iter
@@ -68,50 +119,194 @@ fn parse_input(input: &str) -> Vec<Instruction> {
                 .unwrap()
                 .unwrap();
 
-            std::iter::repeat(direction).take(count)
+            Motion { direction, count }
         })
         .collect()
 }
 
-fn main() {
-    let input = read_to_string("./src/input.txt").unwrap();
-    println!("Solution to part 1 is: {}", part1(&input));
-    println!("Solution to part 2 is: {}", part2(&input));
+/// What to do, and with which input
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Command {
+    Solve,
+    /// Print every step as an ASCII frame
+    Frames,
+    /// Write an animated GIF to the given path
+    Gif(String),
 }
 
-fn part1(input: &str) -> u32 {
-    let instructions = parse_input(&input);
-    let mut head = Position::default();
-    let mut tail = Position::default();
-    let mut positions: HashSet<Position> = HashSet::from([tail]);
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Options {
+    command: Command,
+    input: String,
+    knots: usize,
+    /// Size of a cell in the GIF, in pixels
+    scale: usize,
+}
+
+/// `[frames | gif <path>] [--input <path>] [--knots N] [--scale N]`
+fn parse_args(args: &[String]) -> Result<Options, String> {
+    let mut options = Options {
+        command: Command::Solve,
+        input: String::from("./src/input.txt"),
+        knots: 10,
+        scale: 2,
+    };
 
-    for instruction in instructions {
-        head.go(&instruction);
-        tail.approach(&head);
-        positions.insert(tail);
+    let mut args = args.iter().peekable();
+
+    match args.peek().map(|arg| arg.as_str()) {
+        Some("frames") => {
+            args.next();
+            options.command = Command::Frames;
+        }
+        Some("gif") => {
+            args.next();
+            let path = args.next().ok_or("Missing output path for gif")?;
+            options.command = Command::Gif(path.clone());
+        }
+        _ => {}
+    }
+
+    let positive = |flag: &str, value: Option<&String>| -> Result<usize, String> {
+        value
+            .and_then(|value| value.parse().ok())
+            .filter(|&value| value > 0)
+            .ok_or(format!("{flag} needs a positive number"))
+    };
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--input" => options.input = args.next().ok_or("Missing input path")?.clone(),
+            "--knots" => options.knots = positive("--knots", args.next())?,
+            "--scale" => options.scale = positive("--scale", args.next())?,
+            _ => return Err(format!("Unknown argument {arg}")),
+        }
     }
 
-    positions.len() as u32
+    Ok(options)
 }
 
-fn part2(input: &str) -> u32 {
-    let instructions = parse_input(&input);
-    let mut knots = (0..10).map(|_| Position::default()).collect::<Vec<_>>();
-    // Instantiate the positions set with the tail position (knot 9)
-    let mut positions: HashSet<Position> = HashSet::from([knots[9]]);
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let options = match parse_args(&args) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(1);
+        }
+    };
 
-    for instruction in instructions {
-        knots[0].go(&instruction);
+    let input = read_to_string(&options.input).unwrap();
 
-        for i in 1..10 {
-            let prev = knots[i - 1].clone();
-            knots[i].approach(&prev);
+    match options.command {
+        Command::Frames => {
+            let out = BufWriter::new(io::stdout().lock());
+
+            match visualise::write_frames(&parse_input(&input), options.knots, out) {
+                // Whoever was reading has seen enough
+                Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {}
+                Err(err) => {
+                    eprintln!("Failed to write frames: {err}");
+                    std::process::exit(1);
+                }
+                Ok(()) => {}
+            }
+        }
+        Command::Gif(path) => {
+            let file = BufWriter::new(File::create(path).expect("Failed to create GIF"));
+
+            if let Err(err) =
+                visualise::write_gif(&parse_input(&input), options.knots, file, options.scale)
+            {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
+        }
+        Command::Solve => {
+            println!("Solution to part 1 is: {}", part1(&input));
+            println!("Solution to part 2 is: {}", part2(&input));
+        }
+    }
+}
+
+/// A rope with any number of knots, that keeps track of every cell each of
+/// the knots has visited. The first knot is the head.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rope<const N: usize = 2> {
+    knots: Vec<Position<N>>,
+    trails: Vec<HashSet<Position<N>>>,
+}
+
+impl<const N: usize> Rope<N> {
+    fn new(knots: usize) -> Rope<N> {
+        assert!(knots > 0, "A rope needs at least one knot");
+
+        Rope {
+            knots: vec![Position::default(); knots],
+            trails: vec![HashSet::from([Position::default()]); knots],
+        }
+    }
+
+    /// Move the head by a single step, and have the other knots follow along
+    fn go(&mut self, instruction: &Instruction<N>) {
+        self.knots[0].go(instruction);
+
+        for i in 1..self.knots.len() {
+            let prev = self.knots[i - 1];
+            self.knots[i].approach(&prev);
+        }
+
+        for (trail, knot) in self.trails.iter_mut().zip(self.knots.iter()) {
+            trail.insert(*knot);
+        }
+    }
+
+    /// Apply a whole motion. Once every knot lines up right behind the one
+    /// before it, the rope just slides along as a whole, and we can skip
+    /// figuring out how every knot follows along.
+    fn apply(&mut self, motion: &Motion<N>) {
+        let mut remaining = motion.count;
+
+        while remaining > 0 && !self.is_straight(&motion.direction) {
+            self.go(&motion.direction);
+            remaining -= 1;
         }
 
-        positions.insert(knots[9]);
+        for (trail, knot) in self.trails.iter_mut().zip(self.knots.iter_mut()) {
+            for _ in 0..remaining {
+                knot.go(&motion.direction);
+                trail.insert(*knot);
+            }
+        }
+    }
+
+    /// Whether every knot sits exactly one step behind the previous one
+    fn is_straight(&self, direction: &Instruction<N>) -> bool {
+        self.knots.windows(2).all(|pair| {
+            (0..N).all(|axis| pair[0].coords[axis] - pair[1].coords[axis] == direction.delta[axis])
+        })
+    }
+}
+
+/// The cells visited by every knot of a rope with `knots` knots
+fn simulate<const N: usize>(motions: &[Motion<N>], knots: usize) -> Vec<HashSet<Position<N>>> {
+    let mut rope = Rope::new(knots);
+
+    for motion in motions {
+        rope.apply(motion);
     }
 
-    positions.len() as u32
+    rope.trails
+}
+
+fn part1(input: &str) -> u32 {
+    let trails = simulate(&parse_input::<2>(input), 2);
+    trails.last().unwrap().len() as u32
+}
+
+fn part2(input: &str) -> u32 {
+    let trails = simulate(&parse_input::<2>(input), 10);
+    trails.last().unwrap().len() as u32
 }
 
 #[test]
@@ -125,3 +320,271 @@ fn part2_works() {
     let input = read_to_string("./src/test-input2.txt").unwrap();
     assert_eq!(part2(&input), 36);
 }
+
+#[test]
+fn every_knot_has_a_trail() {
+    let input = read_to_string("./src/test-input.txt").unwrap();
+    let trails = simulate(&parse_input::<2>(&input), 10);
+
+    assert_eq!(trails.len(), 10);
+    // The head and the first knot behind it cover the same ground as in part 1
+    assert_eq!(simulate(&parse_input::<2>(&input), 2)[0], trails[0]);
+    assert_eq!(trails[1].len(), 13);
+    // In the small example, the tail never moves
+    assert_eq!(trails[9], HashSet::from([Position::default()]));
+}
+
+#[test]
+fn frames_draw_the_rope_over_its_trail() {
+    let bounds = visualise::Bounds {
+        min_x: 0,
+        max_x: 5,
+        min_y: 0,
+        max_y: 4,
+    };
+
+    let input = read_to_string("./src/test-input.txt").unwrap();
+    let instructions = parse_input(&input);
+    let mut out = Vec::new();
+    visualise::write_frames(&instructions, 2, &mut out).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    let frames: Vec<&str> = out.trim_end().split("\n\n").collect();
+
+    let steps: usize = instructions.iter().map(|motion| motion.count).sum();
+    assert_eq!(frames.len(), steps + 1);
+    assert_eq!(frames[0], "......\n......\n......\n......\nH.....");
+    // After `R 4`. Unlike the puzzle's diagrams, the tail's trail is filled in
+    // while the rope moves.
+    assert_eq!(frames[4], "......\n......\n......\n......\ns##TH.");
+    // After `U 4`
+    assert_eq!(frames[8], "....H.\n....T.\n....#.\n....#.\ns###..");
+
+    // The tail's trail at the very end, which is the puzzle's last diagram
+    let trail = &simulate(&instructions, 2)[1];
+    assert_eq!(
+        visualise::render_frame(&[], trail, bounds),
+        "..##..\n...##.\n.####.\n....#.\ns###.."
+    );
+}
+
+#[test]
+fn long_motions_slide_the_rope() {
+    // Stepping through every motion one step at a time gives the same trails
+    for path in [
+        "./src/test-input.txt",
+        "./src/test-input2.txt",
+        "./src/input.txt",
+    ] {
+        let motions = parse_input::<2>(&read_to_string(path).unwrap());
+        let mut rope = Rope::new(10);
+
+        for motion in motions.iter() {
+            for _ in 0..motion.count {
+                rope.go(&motion.direction);
+            }
+        }
+
+        assert_eq!(simulate(&motions, 10), rope.trails);
+    }
+
+    let trails = simulate(&parse_input::<2>("R 100000"), 10);
+    assert_eq!(trails[0].len(), 100000 + 1);
+    assert_eq!(trails[9].len(), 100000 - 9 + 1);
+}
+
+#[test]
+fn diagonal_motions() {
+    assert_eq!(Instruction::<2>::try_from("UL").unwrap().delta, [-1, 1]);
+    assert!(Instruction::<2>::try_from("UD").is_err());
+    assert!(Instruction::<2>::try_from("F").is_err());
+
+    // The tail follows diagonally, right behind the head
+    let trails = simulate(&parse_input::<2>("UR 3\nL 2"), 2);
+    assert_eq!(
+        trails[1],
+        HashSet::from([
+            Position::new(0, 0),
+            Position::new(1, 1),
+            Position::new(2, 2)
+        ])
+    );
+}
+
+#[test]
+fn motions_in_3d() {
+    let trails = simulate(&parse_input::<3>("F 4\nUR 2\nDLB 1"), 3);
+
+    let tail = Position { coords: [0, 0, 2] };
+    assert!(trails[2].contains(&tail));
+    assert_eq!(trails[2].len(), 4);
+
+    // Following knots can cut corners along every axis at once
+    let mut knot = Position { coords: [0, 0, 0] };
+    knot.approach(&Position { coords: [2, 1, -1] });
+    assert_eq!(knot.coords, [1, 1, -1]);
+    assert!(knot.adjacent(&Position { coords: [2, 1, -1] }));
+
+    // The 2D answers don't change when we add a third axis
+    let input = read_to_string("./src/test-input2.txt").unwrap();
+    assert_eq!(simulate(&parse_input::<3>(&input), 10)[9].len(), 36);
+}
+
+#[cfg(test)]
+mod properties {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn position() -> impl Strategy<Value = Position> {
+        (-50..50, -50..50).prop_map(|(x, y)| Position::new(x, y))
+    }
+
+    fn motion() -> impl Strategy<Value = Motion> {
+        let directions = vec!["U", "D", "L", "R", "UL", "UR", "DL", "DR"];
+
+        (prop::sample::select(directions), 1..10usize).prop_map(|(direction, count)| Motion {
+            direction: Instruction::try_from(direction).unwrap(),
+            count,
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn approach_catches_up(knot in position(), dx in -2..=2, dy in -2..=2) {
+            // After a single step of the head, it's never more than two cells away
+            let target = Position::new(knot.x() + dx, knot.y() + dy);
+            let mut moved = knot;
+            moved.approach(&target);
+
+            prop_assert!(moved.adjacent(&target));
+            prop_assert!(moved.adjacent(&knot));
+
+            if knot.adjacent(&target) {
+                prop_assert_eq!(moved, knot);
+            }
+        }
+
+        #[test]
+        fn rope_invariants(motions in prop::collection::vec(motion(), 0..20), knots in 1..12usize) {
+            let mut rope = Rope::new(knots);
+
+            for motion in motions.iter() {
+                for _ in 0..motion.count {
+                    let head = rope.knots[0];
+                    let trails = rope.trails.clone();
+                    rope.go(&motion.direction);
+
+                    // The head moves exactly one cell
+                    prop_assert!(head != rope.knots[0] && head.adjacent(&rope.knots[0]));
+
+                    // Every knot stays right next to the one before it
+                    for pair in rope.knots.windows(2) {
+                        prop_assert!(pair[0].adjacent(&pair[1]));
+                    }
+
+                    // Trails only ever grow, and contain where the knots are now
+                    for ((before, after), knot) in trails.iter().zip(rope.trails.iter()).zip(rope.knots.iter()) {
+                        prop_assert!(before.is_subset(after));
+                        prop_assert!(after.contains(knot));
+                    }
+                }
+            }
+
+            // Sliding along whole motions ends up in the same place
+            prop_assert_eq!(simulate(&motions, knots), rope.trails);
+        }
+    }
+}
+
+#[test]
+fn arguments() {
+    let args =
+        |args: &[&str]| parse_args(&args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>());
+
+    let options = args(&["--input", "tiny.txt", "--knots", "2"]).unwrap();
+    assert_eq!(options.command, Command::Solve);
+    assert_eq!(options.input, "tiny.txt");
+    assert_eq!(options.knots, 2);
+
+    let options = args(&["frames", "--knots", "3"]).unwrap();
+    assert_eq!(options.command, Command::Frames);
+    assert_eq!(options.knots, 3);
+
+    let options = args(&["gif", "out.gif", "--scale", "4"]).unwrap();
+    assert_eq!(options.command, Command::Gif(String::from("out.gif")));
+    assert_eq!(options.scale, 4);
+
+    assert!(args(&["--knts", "2"]).is_err());
+    assert!(args(&["--knots", "0"]).is_err());
+    assert!(args(&["--input"]).is_err());
+    assert!(args(&["gif"]).is_err());
+    assert!(args(&["solve"]).is_err());
+}
+
+#[test]
+fn gif_smoke_test() {
+    let input = read_to_string("./src/test-input.txt").unwrap();
+    let motions = parse_input(&input);
+    let mut out = Vec::new();
+    visualise::write_gif(&motions, 2, &mut out, 3).unwrap();
+
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::Indexed);
+    let mut decoder = options.read_info(out.as_slice()).unwrap();
+    assert_eq!((decoder.width(), decoder.height()), (6 * 3, 5 * 3));
+
+    // Put the frames on top of each other, the way a viewer would
+    let (width, height) = (decoder.width() as usize, decoder.height() as usize);
+    let mut canvas = vec![u8::MAX; width * height];
+    let mut frames = 0;
+
+    while let Some(frame) = decoder.read_next_frame().unwrap() {
+        for (idx, &pixel) in frame.buffer.iter().enumerate() {
+            let x = frame.left as usize + idx % frame.width as usize;
+            let y = frame.top as usize + idx / frame.width as usize;
+            canvas[y * width + x] = pixel;
+        }
+
+        frames += 1;
+    }
+
+    let steps: usize = motions.iter().map(|motion| motion.count).sum();
+    assert_eq!(frames, steps + 1);
+
+    // The last frame looks just like the last ASCII one
+    let mut ascii = Vec::new();
+    visualise::write_frames(&motions, 2, &mut ascii).unwrap();
+    let ascii = String::from_utf8(ascii).unwrap();
+    let last = ascii.trim_end().rsplit("\n\n").next().unwrap();
+
+    for (row, line) in last.lines().enumerate() {
+        for (col, c) in line.chars().enumerate() {
+            let expected = match c {
+                '.' => 0,
+                '#' => 1,
+                's' => 2,
+                'H' => 3,
+                _ => 4,
+            };
+
+            assert_eq!(
+                canvas[row * 3 * width + col * 3],
+                expected,
+                "{c} at ({col}, {row})"
+            );
+        }
+    }
+}
+
+#[test]
+fn gif_rejects_huge_bounds() {
+    let motions = parse_input("R 40000\n");
+    let result = visualise::write_gif(&motions, 2, io::sink(), 2);
+
+    assert!(matches!(
+        result,
+        Err(visualise::GifError::TooLarge {
+            width: 80002,
+            height: 2
+        })
+    ));
+}
@@ -0,0 +1,268 @@
+/*
+ * Drawing the rope, the way the puzzle statement does
+ */
+
+use std::{
+    collections::HashSet,
+    fmt::{self, Display, Formatter},
+    io::{self, Write},
+};
+
+use crate::{Motion, Position, Rope};
+
+/// The area we draw, inclusive on both ends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub min_x: i32,
+    pub max_x: i32,
+    pub min_y: i32,
+    pub max_y: i32,
+}
+
+impl Bounds {
+    /// The smallest area containing the starting point and every cell the head
+    /// visits. The other knots never leave that area.
+    pub fn around(trail: &HashSet<Position>) -> Bounds {
+        let start = Position::default();
+
+        trail.iter().fold(
+            Bounds {
+                min_x: start.x(),
+                max_x: start.x(),
+                min_y: start.y(),
+                max_y: start.y(),
+            },
+            |bounds, pos| Bounds {
+                min_x: bounds.min_x.min(pos.x()),
+                max_x: bounds.max_x.max(pos.x()),
+                min_y: bounds.min_y.min(pos.y()),
+                max_y: bounds.max_y.max(pos.y()),
+            },
+        )
+    }
+
+    fn width(&self) -> usize {
+        (self.max_x - self.min_x + 1) as usize
+    }
+
+    fn height(&self) -> usize {
+        (self.max_y - self.min_y + 1) as usize
+    }
+
+    /// The column and row of `pos`
+    fn index(&self, pos: Position) -> (usize, usize) {
+        (
+            (pos.x() - self.min_x) as usize,
+            (self.max_y - pos.y()) as usize,
+        )
+    }
+
+    /// Every cell, row by row, with the highest `y` on top
+    fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = Position>> + '_ {
+        (self.min_y..=self.max_y)
+            .rev()
+            .map(|y| (self.min_x..=self.max_x).map(move |x| Position::new(x, y)))
+    }
+}
+
+/// What a single cell in a frame holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Empty,
+    Trail,
+    Start,
+    Knot(usize),
+}
+
+fn cell(pos: Position, knots: &[Position], trail: &HashSet<Position>) -> Cell {
+    // Knots closer to the head are drawn on top
+    if let Some(idx) = knots.iter().position(|knot| *knot == pos) {
+        Cell::Knot(idx)
+    } else if pos == Position::default() {
+        Cell::Start
+    } else if trail.contains(&pos) {
+        Cell::Trail
+    } else {
+        Cell::Empty
+    }
+}
+
+/// Draw the knots (`H`, then `1`, `2`, ... or `T` for a two-knot rope), the
+/// starting point `s` and the cells in `trail` as `#`.
+pub fn render_frame(knots: &[Position], trail: &HashSet<Position>, bounds: Bounds) -> String {
+    bounds
+        .rows()
+        .map(|row| {
+            row.map(|pos| match cell(pos, knots, trail) {
+                Cell::Knot(0) => 'H',
+                Cell::Knot(_) if knots.len() == 2 => 'T',
+                Cell::Knot(idx) => char::from_digit(idx as u32, 10).unwrap_or('*'),
+                Cell::Start => 's',
+                Cell::Trail => '#',
+                Cell::Empty => '.',
+            })
+            .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Run the simulation, calling `draw` with the rope before the first step
+/// and after every step.
+fn animate(motions: &[Motion], knots: usize, mut draw: impl FnMut(&Rope, Bounds)) {
+    let bounds = Bounds::around(&crate::simulate(motions, 1)[0]);
+    let mut rope = Rope::new(knots);
+    draw(&rope, bounds);
+
+    for motion in motions {
+        for _ in 0..motion.count {
+            rope.go(&motion.direction);
+            draw(&rope, bounds);
+        }
+    }
+}
+
+/// Write one ASCII frame per step, with the tail's trail filled in, and a
+/// blank line after every frame. Frames are written as we go, since a long
+/// input has thousands of them.
+pub fn write_frames(motions: &[Motion], knots: usize, mut out: impl Write) -> io::Result<()> {
+    let mut result = Ok(());
+
+    animate(motions, knots, |rope, bounds| {
+        if result.is_ok() {
+            let frame = render_frame(&rope.knots, rope.trails.last().unwrap(), bounds);
+            result = writeln!(out, "{frame}\n");
+        }
+    });
+
+    result
+}
+
+#[derive(Debug)]
+pub enum GifError {
+    /// GIFs can't be wider or taller than `u16::MAX` pixels
+    TooLarge {
+        width: usize,
+        height: usize,
+    },
+    Encoding(gif::EncodingError),
+}
+
+impl Display for GifError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GifError::TooLarge { width, height } => write!(
+                f,
+                "A {width}×{height} GIF is too large, try a smaller scale"
+            ),
+            GifError::Encoding(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl From<gif::EncodingError> for GifError {
+    fn from(err: gif::EncodingError) -> Self {
+        GifError::Encoding(err)
+    }
+}
+
+/// Background, trail, start, head and the rest of the knots
+const PALETTE: &[u8] = &[
+    0x1e, 0x1e, 0x2e, // Empty
+    0x58, 0x5b, 0x70, // Trail
+    0xa6, 0xe3, 0xa1, // Start
+    0xf3, 0x8b, 0xa8, // Head
+    0xf9, 0xe2, 0xaf, // Other knots
+];
+
+fn palette_index(cell: Cell) -> u8 {
+    match cell {
+        Cell::Empty => 0,
+        Cell::Trail => 1,
+        Cell::Start => 2,
+        Cell::Knot(0) => 3,
+        Cell::Knot(_) => 4,
+    }
+}
+
+/// Write an animated GIF with a frame per step. Every cell is drawn as a
+/// `scale` × `scale` square.
+///
+/// Only the cells a knot left or moved into can change in a step, so after
+/// the first frame we just draw the smallest rectangle around the ones that
+/// did, on top of the previous frame.
+pub fn write_gif(
+    motions: &[Motion],
+    knots: usize,
+    writer: impl Write,
+    scale: usize,
+) -> Result<(), GifError> {
+    assert!(scale > 0, "The scale has to be positive");
+
+    let bounds = Bounds::around(&crate::simulate(motions, 1)[0]);
+    let (width, height) = (bounds.width() * scale, bounds.height() * scale);
+    let (Ok(gif_width), Ok(gif_height)) = (u16::try_from(width), u16::try_from(height)) else {
+        return Err(GifError::TooLarge { width, height });
+    };
+
+    let mut encoder = gif::Encoder::new(writer, gif_width, gif_height, PALETTE)?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+
+    // What's currently on screen, `None` until the first frame is drawn
+    let mut screen: Vec<Vec<Option<u8>>> = vec![vec![None; bounds.width()]; bounds.height()];
+    let mut previous: Vec<Position> = Vec::new();
+    let mut result = Ok(());
+
+    animate(motions, knots, |rope, bounds| {
+        if result.is_err() {
+            return;
+        }
+
+        let trail = rope.trails.last().unwrap();
+        let dirty: Vec<Position> = if previous.is_empty() {
+            bounds.rows().flatten().collect()
+        } else {
+            previous.iter().chain(rope.knots.iter()).copied().collect()
+        };
+
+        // The columns and rows of the cells that changed, inclusive
+        let mut changed: Option<(usize, usize, usize, usize)> = None;
+
+        for pos in dirty {
+            let (col, row) = bounds.index(pos);
+            let value = palette_index(cell(pos, &rope.knots, trail));
+
+            if screen[row][col] != Some(value) {
+                screen[row][col] = Some(value);
+                changed = Some(match changed {
+                    None => (col, row, col, row),
+                    Some((left, top, right, bottom)) => {
+                        (left.min(col), top.min(row), right.max(col), bottom.max(row))
+                    }
+                });
+            }
+        }
+
+        previous = rope.knots.clone();
+
+        // Even if nothing changed, we still want a frame for the step, so we
+        // redraw a single cell
+        let (left, top, right, bottom) = changed.unwrap_or((0, 0, 0, 0));
+        let (frame_width, frame_height) = ((right - left + 1) * scale, (bottom - top + 1) * scale);
+
+        let pixels: Vec<u8> = (top * scale..(bottom + 1) * scale)
+            .flat_map(|y| (left * scale..(right + 1) * scale).map(move |x| (x / scale, y / scale)))
+            .map(|(col, row)| screen[row][col].unwrap())
+            .collect();
+
+        let mut frame =
+            gif::Frame::from_indexed_pixels(frame_width as u16, frame_height as u16, pixels, None);
+        frame.left = (left * scale) as u16;
+        frame.top = (top * scale) as u16;
+        frame.dispose = gif::DisposalMethod::Keep;
+        frame.delay = 5; // in hundredths of a second
+        result = encoder.write_frame(&frame).map_err(GifError::from);
+    });
+
+    result
+}